@@ -0,0 +1,342 @@
+use scale::Decode;
+use serde::{Deserialize, Serialize};
+use substrate_subxt::{
+    balances::{BalancesEventsDecoder, TransferCall},
+    sp_core::{Encode, H256},
+    sp_runtime::{
+        traits::{BlakeTwo256, Hash},
+        DispatchError,
+    },
+    ClientBuilder, Error, EventsDecoder, IndracoreNodeRuntime, RawEvent, Signer,
+};
+
+use crate::{
+    balance::units::Balance,
+    extrinsic::{self, TxOptions},
+    fee,
+    frame::utility::{BatchAllCall, BatchCall},
+    keyring::Sr25519,
+    primitives,
+    signer::ToSigner,
+};
+
+/// Weight a block can hold if the chain does not expose `System::MaximumBlockWeight`.
+const DEFAULT_MAXIMUM_BLOCK_WEIGHT: u64 = 2_000_000_000_000;
+
+/// Many transfers from one sender, packed into `utility.batch` or `utility.batch_all`
+/// extrinsics small enough to fit in a block.
+pub struct BatchTransfer<S: ToSigner = Sr25519> {
+    pub sender: S,
+    pub transfers: Vec<(primitives::IndracoreId, Balance)>,
+    /// use `batch_all`, so a failing transfer reverts the rest of its chunk
+    pub atomic: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ItemStatus {
+    Completed,
+    Failed(String),
+    /// an earlier transfer of the same chunk failed and stopped the batch, or
+    /// an earlier chunk was not settled and the chunk was never submitted
+    NotExecuted,
+    /// the chunk was submitted but its outcome was lost, e.g. by a timeout, it
+    /// can still be included until block `death`, or at any time if immortal
+    Unknown {
+        death: Option<u32>,
+    },
+}
+
+/// The outcome of one transfer of a batch.
+#[derive(Clone, Debug)]
+pub struct BatchItem {
+    pub reciever: primitives::IndracoreId,
+    pub amount: Balance,
+    pub status: ItemStatus,
+    /// the batch extrinsic the transfer was part of, if it was submitted
+    pub extrinsic_hash: Option<H256>,
+    pub block_hash: Option<H256>,
+}
+
+impl<S: ToSigner> BatchTransfer<S> {
+    pub fn run(&self, pass: Option<&str>) -> Result<Vec<BatchItem>, Error> {
+        self.run_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `run`, with the era, tip, nonce and wait condition of `options` for
+    /// every chunk, `options.nonce` is the nonce of the first chunk and the next
+    /// chunks take the nonces after it
+    ///
+    /// Once a chunk is not settled, because it could not be submitted or its
+    /// outcome is `Unknown`, the chunks after it are not submitted, so neither a
+    /// retry pays twice nor a skipped nonce holds them back. Set
+    /// `options.mortality` to know when an `Unknown` chunk expires.
+    pub fn run_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<Vec<BatchItem>, Error> {
+        let sender = self.sender.to_signer(pass)?;
+        if self.transfers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
+                Err(e) => return Err(e),
+            };
+
+            let (to, amount) = &self.transfers[0];
+            let item = fee::estimate(TransferCall {
+                to,
+                amount: amount.plancks(),
+            })
+            .await?;
+            let size = chunk_size(item.weight, maximum_block_weight(&client));
+
+            let mut items = Vec::with_capacity(self.transfers.len());
+            let mut settled = true;
+            for (index, chunk) in self.transfers.chunks(size).enumerate() {
+                let (statuses, extrinsic_hash, block_hash) = if settled {
+                    let options = TxOptions {
+                        nonce: options.nonce.map(|nonce| nonce + index as u32),
+                        ..options.clone()
+                    };
+                    self.submit(&client, &sender, chunk, &options).await
+                } else {
+                    (vec![ItemStatus::NotExecuted; chunk.len()], None, None)
+                };
+                settled = is_settled(extrinsic_hash, &statuses);
+                for ((reciever, amount), status) in chunk.iter().zip(statuses) {
+                    items.push(BatchItem {
+                        reciever: reciever.clone(),
+                        amount: *amount,
+                        status,
+                        extrinsic_hash,
+                        block_hash,
+                    });
+                }
+            }
+            Ok(items)
+        })
+    }
+
+    /// sign and submit one chunk, returning the status of each of its transfers
+    /// and the extrinsic and block hashes as far as they are known
+    async fn submit(
+        &self,
+        client: &primitives::Client,
+        sender: &S::Signer,
+        chunk: &[(primitives::IndracoreId, Balance)],
+        options: &TxOptions,
+    ) -> (Vec<ItemStatus>, Option<H256>, Option<H256>) {
+        let (encoded, death, mut decoder) = match self.sign(client, sender, chunk, options).await {
+            Ok(signed) => signed,
+            Err(e) => {
+                let statuses = vec![ItemStatus::Failed(format!("{:?}", e)); chunk.len()];
+                return (statuses, None, None);
+            }
+        };
+        decoder.with_balances();
+        let extrinsic_hash = BlakeTwo256::hash(&encoded);
+
+        match extrinsic::submit_and_watch_encoded(client, encoded, decoder, options).await {
+            Ok(included) => (
+                item_statuses(chunk.len(), &included.events),
+                Some(extrinsic_hash),
+                Some(included.block_hash),
+            ),
+            // the batch_all was included and failed, none of its transfers happened
+            Err(Error::Runtime(e)) => (
+                vec![ItemStatus::Failed(format!("{:?}", e)); chunk.len()],
+                Some(extrinsic_hash),
+                None,
+            ),
+            Err(_) => (
+                vec![ItemStatus::Unknown { death }; chunk.len()],
+                Some(extrinsic_hash),
+                None,
+            ),
+        }
+    }
+
+    /// the encoded `batch` or `batch_all` extrinsic of `chunk`, the block it
+    /// expires at if it is mortal, and the decoder of its events
+    async fn sign(
+        &self,
+        client: &primitives::Client,
+        sender: &S::Signer,
+        chunk: &[(primitives::IndracoreId, Balance)],
+        options: &TxOptions,
+    ) -> Result<(Vec<u8>, Option<u32>, EventsDecoder<IndracoreNodeRuntime>), Error> {
+        let mut calls = Vec::with_capacity(chunk.len());
+        for (to, amount) in chunk {
+            calls.push(client.encode(TransferCall {
+                to,
+                amount: amount.plancks(),
+            })?);
+        }
+
+        let nonce = options.nonce.or_else(|| sender.nonce());
+        let params = extrinsic::signing_params(client, sender.account_id(), nonce, options).await?;
+        let death = if params.era.is_immortal() {
+            None
+        } else {
+            let birth = extrinsic::block_number(client, params.block_hash).await?;
+            Some(params.era.death(birth as u64) as u32)
+        };
+
+        if self.atomic {
+            let call = BatchAllCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            let extrinsic = extrinsic::sign(client, call, sender, &params).await?;
+            let decoder = client.events_decoder::<BatchAllCall<IndracoreNodeRuntime>>();
+            Ok((extrinsic.encode(), death, decoder))
+        } else {
+            let call = BatchCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            let extrinsic = extrinsic::sign(client, call, sender, &params).await?;
+            let decoder = client.events_decoder::<BatchCall<IndracoreNodeRuntime>>();
+            Ok((extrinsic.encode(), death, decoder))
+        }
+    }
+}
+
+pub(crate) fn maximum_block_weight(client: &primitives::Client) -> u64 {
+    client
+        .metadata()
+        .module("System")
+        .and_then(|module| module.constant("MaximumBlockWeight"))
+        .and_then(|constant| constant.value::<u64>())
+        .unwrap_or(DEFAULT_MAXIMUM_BLOCK_WEIGHT)
+}
+
+/// How many items of `item_weight` fit in one batch. A batch uses at most a
+/// quarter of the block, leaving room for the operational share and other extrinsics.
+pub(crate) fn chunk_size(item_weight: u64, maximum_block_weight: u64) -> usize {
+    let limit = maximum_block_weight / 4;
+    std::cmp::max(1, limit / std::cmp::max(1, item_weight)) as usize
+}
+
+/// Whether a chunk used its nonce and has a known outcome. A chunk that failed
+/// before it was submitted has no extrinsic hash.
+fn is_settled(extrinsic_hash: Option<H256>, statuses: &[ItemStatus]) -> bool {
+    extrinsic_hash.is_some()
+        && !statuses
+            .iter()
+            .any(|status| matches!(status, ItemStatus::Unknown { .. }))
+}
+
+/// Per item outcome of a batch from its `ItemCompleted`, `BatchInterrupted` and
+/// `BatchCompleted` events. Runtimes without `ItemCompleted` only report the batch.
+pub(crate) fn item_statuses(len: usize, events: &[RawEvent]) -> Vec<ItemStatus> {
+    let mut statuses = vec![ItemStatus::NotExecuted; len];
+    let mut completed = 0;
+    for event in events.iter().filter(|e| e.module == "Utility") {
+        match event.variant.as_str() {
+            "ItemCompleted" => {
+                if completed < len {
+                    statuses[completed] = ItemStatus::Completed;
+                }
+                completed += 1;
+            }
+            "BatchInterrupted" => {
+                let mut data = &event.data[..];
+                let index = u32::decode(&mut data).unwrap_or(completed as u32) as usize;
+                let error = match DispatchError::decode(&mut data) {
+                    Ok(e) => format!("{:?}", e),
+                    Err(_) => "Batch interrupted".to_string(),
+                };
+                for status in statuses.iter_mut().take(index) {
+                    *status = ItemStatus::Completed;
+                }
+                if index < len {
+                    statuses[index] = ItemStatus::Failed(error);
+                }
+            }
+            "BatchCompleted" => {
+                for status in statuses.iter_mut() {
+                    *status = ItemStatus::Completed;
+                }
+            }
+            _ => continue,
+        }
+    }
+    statuses
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::batch::{chunk_size, is_settled, item_statuses, ItemStatus};
+    use scale::Encode;
+    use substrate_subxt::{sp_core::H256, sp_runtime::DispatchError, RawEvent};
+
+    fn event(variant: &str, data: Vec<u8>) -> RawEvent {
+        RawEvent {
+            module: "Utility".into(),
+            variant: variant.into(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_chunk_size() {
+        assert_eq!(chunk_size(200_000_000, 2_000_000_000_000), 2500);
+        assert_eq!(chunk_size(0, 2_000_000_000_000), 500_000_000_000);
+        assert_eq!(chunk_size(u64::max_value(), 2_000_000_000_000), 1);
+    }
+
+    #[test]
+    fn test_item_statuses_completed() {
+        let events = vec![
+            event("ItemCompleted", vec![]),
+            event("ItemCompleted", vec![]),
+            event("BatchCompleted", vec![]),
+        ];
+        assert_eq!(item_statuses(2, &events), vec![ItemStatus::Completed; 2]);
+        // runtimes without ItemCompleted
+        assert_eq!(
+            item_statuses(2, &[event("BatchCompleted", vec![])]),
+            vec![ItemStatus::Completed; 2]
+        );
+    }
+
+    #[test]
+    fn test_item_statuses_interrupted() {
+        let mut data = 1u32.encode();
+        data.extend(DispatchError::Other("").encode());
+        let events = vec![
+            event("ItemCompleted", vec![]),
+            event("BatchInterrupted", data),
+        ];
+        let statuses = item_statuses(3, &events);
+        assert_eq!(statuses[0], ItemStatus::Completed);
+        assert!(matches!(statuses[1], ItemStatus::Failed(_)));
+        assert_eq!(statuses[2], ItemStatus::NotExecuted);
+    }
+
+    #[test]
+    fn test_is_settled() {
+        let hash = Some(H256::repeat_byte(1));
+        let failed = vec![ItemStatus::Failed("Module".into()); 2];
+        assert!(is_settled(
+            hash,
+            &[ItemStatus::Completed, ItemStatus::NotExecuted]
+        ));
+        assert!(is_settled(hash, &failed));
+        // failed before it was submitted, its nonce is still free
+        assert!(!is_settled(None, &failed));
+        assert!(!is_settled(
+            hash,
+            &[ItemStatus::Unknown { death: Some(74) }]
+        ));
+    }
+}
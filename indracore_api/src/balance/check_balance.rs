@@ -1,14 +1,52 @@
-use crate::primitives;
+use crate::{
+    balance::units::Balance,
+    frame::vesting::{VestingInfo, VestingStore},
+    primitives, rpc,
+};
+use std::{cmp, collections::HashMap};
 use substrate_subxt::{
-    balances::*, sp_core::crypto::AccountId32, system::*, ClientBuilder, Error,
-    IndracoreNodeRuntime,
+    balances::*,
+    sp_core::{crypto::AccountId32, storage::StorageChangeSet, Decode, H256},
+    system::*,
+    ClientBuilder, Error, IndracoreNodeRuntime, Store,
 };
 
+pub use substrate_subxt::balances::Reasons;
+
 pub struct BalanceInfo {
-    pub free: u128,
-    pub reserved: u128,
-    pub misc_frozen: u128,
-    pub fee_frozen: u128,
+    pub free: Balance,
+    pub reserved: Balance,
+    pub misc_frozen: Balance,
+    pub fee_frozen: Balance,
+    /// what can be transferred while keeping the account alive
+    pub transferable: Balance,
+    pub locks: Vec<Lock>,
+    /// empty if the chain has no vesting pallet
+    pub vesting: Vec<VestingSchedule>,
+}
+
+/// A `Balances::Locks` entry, `id` is the 8 byte lock identifier, e.g. `staking`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lock {
+    pub id: String,
+    pub amount: Balance,
+    pub reasons: Reasons,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingSchedule {
+    pub locked: Balance,
+    pub per_block: Balance,
+    pub starting_block: u32,
+}
+
+impl VestingSchedule {
+    /// the amount still locked at block `number`
+    pub fn locked_at(&self, number: u32) -> Balance {
+        let vested = (number.saturating_sub(self.starting_block) as u128)
+            .saturating_mul(self.per_block.plancks());
+        self.locked.plancks().saturating_sub(vested).into()
+    }
 }
 
 pub async fn total_issuance() -> Result<u128, Error> {
@@ -41,7 +79,6 @@ pub async fn free_balance(id: AccountId32) -> Result<u128, Error> {
         Ok(info) => info,
         Err(e) => return Err(e),
     };
-    println!("{:?}", info);
     Ok(info.data.free)
 }
 
@@ -59,11 +96,199 @@ pub async fn balance_info(id: AccountId32) -> Result<BalanceInfo, Error> {
         Ok(info) => info,
         Err(e) => return Err(e),
     };
+    balance_info_at(&client, &id, info, None).await
+}
+
+/// Complete the account data `info` of `id` with its locks and vesting at block `at`.
+pub(crate) async fn balance_info_at(
+    client: &primitives::Client,
+    id: &AccountId32,
+    info: AccountInfo<IndracoreNodeRuntime>,
+    at: Option<H256>,
+) -> Result<BalanceInfo, Error> {
+    let locks = match client.locks(id, at).await {
+        Ok(locks) => locks,
+        Err(e) => return Err(e),
+    };
+    let vesting = if client.metadata().module("Vesting").is_ok() {
+        client.fetch(&VestingStore { account_id: id }, at).await?
+    } else {
+        None
+    };
+    let existential_deposit = existential_deposit_of(client)?;
+    Ok(build_balance_info(
+        info,
+        locks,
+        vesting,
+        existential_deposit,
+    ))
+}
+
+/// Keys fetched per `state_queryStorageAt` request, to stay below the node's
+/// request size limit.
+const QUERY_CHUNK: usize = 512;
+
+/// fetch the balances of all `ids` at the best block, without one round-trip per account
+pub async fn balance_infos(
+    ids: &[AccountId32],
+) -> Result<HashMap<AccountId32, BalanceInfo>, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+    let existential_deposit = existential_deposit_of(&client)?;
+    let has_vesting = client.metadata().module("Vesting").is_ok();
+
+    let metadata = client.metadata();
+    let mut keys = Vec::new();
+    for id in ids {
+        keys.push(AccountStore::<IndracoreNodeRuntime> { account_id: id }.key(metadata)?);
+        keys.push(LocksStore::<IndracoreNodeRuntime> { account_id: id }.key(metadata)?);
+        if has_vesting {
+            keys.push(VestingStore { account_id: id }.key(metadata)?);
+        }
+    }
+
+    // every chunk reads the same block so the result is a consistent snapshot
+    let at = match client.block_hash(None).await {
+        Ok(Some(hash)) => hash,
+        Ok(None) => return Err(Error::Other("best block hash not found".into())),
+        Err(e) => return Err(e),
+    };
+    let rpc = rpc::client().await?;
+    let mut values = HashMap::new();
+    for chunk in keys.chunks(QUERY_CHUNK) {
+        let sets: Vec<StorageChangeSet<H256>> = rpc::request(
+            &rpc,
+            "state_queryStorageAt",
+            vec![rpc::json(chunk)?, rpc::json(at)?],
+        )
+        .await?;
+        for set in sets {
+            for (key, data) in set.changes {
+                if let Some(data) = data {
+                    values.insert(key, data.0);
+                }
+            }
+        }
+    }
+
+    let mut keys = keys.into_iter();
+    let mut infos = HashMap::new();
+    for id in ids {
+        // missing entries are accounts that do not exist or have no locks or vesting
+        let info = match keys.next().and_then(|key| values.get(&key)) {
+            Some(data) => AccountInfo::<IndracoreNodeRuntime>::decode(&mut &data[..])?,
+            None => Default::default(),
+        };
+        let locks = match keys.next().and_then(|key| values.get(&key)) {
+            Some(data) => Vec::<BalanceLock<u128>>::decode(&mut &data[..])?,
+            None => Vec::new(),
+        };
+        let vesting = if has_vesting {
+            match keys.next().and_then(|key| values.get(&key)) {
+                Some(data) => Some(VestingInfo::<u128, u32>::decode(&mut &data[..])?),
+                None => None,
+            }
+        } else {
+            None
+        };
+        infos.insert(
+            id.clone(),
+            build_balance_info(info, locks, vesting, existential_deposit),
+        );
+    }
+    Ok(infos)
+}
+
+fn build_balance_info(
+    info: AccountInfo<IndracoreNodeRuntime>,
+    locks: Vec<BalanceLock<u128>>,
+    vesting: Option<VestingInfo<u128, u32>>,
+    existential_deposit: u128,
+) -> BalanceInfo {
+    BalanceInfo {
+        free: info.data.free.into(),
+        misc_frozen: info.data.misc_frozen.into(),
+        reserved: info.data.reserved.into(),
+        fee_frozen: info.data.fee_frozen.into(),
+        transferable: transferable(
+            info.data.free,
+            cmp::max(info.data.misc_frozen, info.data.fee_frozen),
+            existential_deposit,
+        )
+        .into(),
+        locks: locks
+            .into_iter()
+            .map(|lock| Lock {
+                id: String::from_utf8_lossy(&lock.id).trim_end().to_string(),
+                amount: lock.amount.into(),
+                reasons: lock.reasons,
+            })
+            .collect(),
+        vesting: vesting
+            .into_iter()
+            .map(|info: VestingInfo<u128, u32>| VestingSchedule {
+                locked: info.locked.into(),
+                per_block: info.per_block.into(),
+                starting_block: info.starting_block,
+            })
+            .collect(),
+    }
+}
+
+/// Frozen funds may count toward the existential deposit, so the account stays
+/// alive as long as `max(frozen, existential_deposit)` is left.
+pub(crate) fn transferable(free: u128, frozen: u128, existential_deposit: u128) -> u128 {
+    free.saturating_sub(cmp::max(frozen, existential_deposit))
+}
+
+/// the minimum balance an account must keep to stay alive
+pub async fn existential_deposit() -> Result<u128, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+    existential_deposit_of(&client)
+}
+
+pub(crate) fn existential_deposit_of(client: &primitives::Client) -> Result<u128, Error> {
+    let constant = client
+        .metadata()
+        .module("Balances")?
+        .constant("ExistentialDeposit")?;
+    Ok(constant.value::<u128>()?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::check_balance::{transferable, VestingSchedule};
+
+    #[test]
+    fn test_transferable() {
+        assert_eq!(transferable(1_000, 0, 100), 900);
+        assert_eq!(transferable(1_000, 300, 100), 700);
+        assert_eq!(transferable(50, 0, 100), 0);
+        assert_eq!(transferable(1_000, 1_500, 100), 0);
+    }
 
-    Ok(BalanceInfo {
-        free: info.data.free,
-        misc_frozen: info.data.misc_frozen,
-        reserved: info.data.reserved,
-        fee_frozen: info.data.fee_frozen,
-    })
+    #[test]
+    fn test_vesting_locked_at() {
+        let schedule = VestingSchedule {
+            locked: 1_000.into(),
+            per_block: 10.into(),
+            starting_block: 100,
+        };
+        assert_eq!(schedule.locked_at(50).plancks(), 1_000);
+        assert_eq!(schedule.locked_at(150).plancks(), 500);
+        assert_eq!(schedule.locked_at(500).plancks(), 0);
+    }
 }
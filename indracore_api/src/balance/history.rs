@@ -0,0 +1,844 @@
+use crate::{balance::units::Balance, frame::events, indices, primitives, rpc};
+use pallet_indices::address::Address;
+use rusqlite::{params, types::ToSql, Connection, OptionalExtension};
+use scale::{Codec, Compact};
+use std::{
+    cmp,
+    collections::HashMap,
+    convert::TryFrom,
+    ops::RangeInclusive,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use substrate_subxt::{
+    balances::TransferEvent,
+    sp_core::{crypto::AccountId32, Decode, Encode, H256},
+    sp_runtime::{generic::Era, traits::Header, MultiSignature},
+    system::Phase,
+    ClientBuilder, Error, EventsDecoder, IndracoreNodeRuntime, Metadata, Raw,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecordKind {
+    /// a `Balances::Transfer` event
+    Transfer,
+    /// a `Balances::Deposit` event, e.g. the block author share of fees
+    Deposit,
+    /// the fee and tip paid by the signer of an extrinsic, as estimated by
+    /// `payment_queryInfo` at the parent block; weight refunded after dispatch
+    /// is not deducted, so the fee charged can be lower
+    Fee,
+}
+
+impl RecordKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RecordKind::Transfer => "transfer",
+            RecordKind::Deposit => "deposit",
+            RecordKind::Fee => "fee",
+        }
+    }
+
+    fn parse(kind: &str) -> Result<Self, Error> {
+        match kind {
+            "transfer" => Ok(RecordKind::Transfer),
+            "deposit" => Ok(RecordKind::Deposit),
+            "fee" => Ok(RecordKind::Fee),
+            kind => Err(Error::Other(format!("Unknown record kind '{}'", kind))),
+        }
+    }
+}
+
+/// A balance movement found in a block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    pub block_number: u32,
+    pub block_hash: H256,
+    /// the `Timestamp::Now` of the block
+    pub timestamp: SystemTime,
+    /// `None` for movements outside of extrinsics, e.g. at block initialization
+    pub extrinsic_index: Option<u32>,
+    pub kind: RecordKind,
+    /// the sender of a transfer or payer of a fee
+    pub from: Option<AccountId32>,
+    /// the reciever of a transfer or deposit
+    pub to: Option<AccountId32>,
+    pub amount: Balance,
+    /// the remark sent along with a transfer by `Transaction::run_with_memo`
+    pub memo: Option<Vec<u8>>,
+}
+
+/// Which records `History::query` returns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Query {
+    /// records the account sent, recieved or paid
+    pub account: AccountId32,
+    /// earliest block timestamp, inclusive
+    pub from: Option<SystemTime>,
+    /// latest block timestamp, exclusive
+    pub to: Option<SystemTime>,
+    /// only transfers between `account` and this account
+    pub counterparty: Option<AccountId32>,
+}
+
+impl Query {
+    /// every record of `account`
+    pub fn new(account: AccountId32) -> Self {
+        Query {
+            account,
+            from: None,
+            to: None,
+            counterparty: None,
+        }
+    }
+}
+
+/// Registers an event argument type with an `EventsDecoder`.
+type RegisterType = Box<dyn Fn(&mut EventsDecoder<IndracoreNodeRuntime>) + Send + Sync>;
+
+/// A local SQLite database of the transfers, deposits and fees of every account,
+/// filled block by block with `index`.
+pub struct History {
+    conn: Connection,
+    types: Vec<RegisterType>,
+}
+
+impl History {
+    /// open the database at `path`, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let conn = db(Connection::open(path))?;
+        db(conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS records (
+                id INTEGER PRIMARY KEY,
+                block_number INTEGER NOT NULL,
+                block_hash BLOB NOT NULL,
+                timestamp INTEGER NOT NULL,
+                extrinsic_index INTEGER,
+                kind TEXT NOT NULL,
+                source BLOB,
+                dest BLOB,
+                amount TEXT NOT NULL,
+                memo BLOB
+            );
+            CREATE INDEX IF NOT EXISTS records_source ON records (source, timestamp);
+            CREATE INDEX IF NOT EXISTS records_dest ON records (dest, timestamp);
+            CREATE INDEX IF NOT EXISTS records_block ON records (block_number);
+            CREATE TABLE IF NOT EXISTS progress (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                next_block INTEGER NOT NULL
+            );",
+        ))?;
+        // databases created before memos were indexed
+        if conn.prepare("SELECT memo FROM records LIMIT 0").is_err() {
+            db(conn.execute_batch("ALTER TABLE records ADD COLUMN memo BLOB"))?;
+        }
+        Ok(History {
+            conn,
+            types: Vec::new(),
+        })
+    }
+
+    /// the first block not indexed yet, `None` if nothing was indexed
+    pub fn next_block(&self) -> Result<Option<u32>, Error> {
+        db(self
+            .conn
+            .query_row(
+                "SELECT next_block FROM progress WHERE id = 0",
+                params![],
+                |row| row.get(0),
+            )
+            .optional())
+    }
+
+    /// decode event arguments named `name` as `U`, for runtimes with event
+    /// types `index` does not know
+    pub fn register_type_size<U>(&mut self, name: &str)
+    where
+        U: Codec + Default + Send + Sync + 'static,
+    {
+        let name = name.to_string();
+        self.types.push(Box::new(move |decoder| {
+            decoder.register_type_size::<U>(&name);
+        }));
+    }
+
+    /// Index at most `max_blocks` finalized blocks from `start`, or from where
+    /// the last run stopped if that is later. Returns the number of blocks
+    /// indexed, call it again until it returns 0 to catch up with the chain.
+    /// Each block is stored as soon as it is indexed.
+    ///
+    /// Every event of a block is decoded to find the balance events. Before any
+    /// block is indexed, the event types of every runtime module are checked,
+    /// and indexing fails with the types it cannot decode, see
+    /// `register_type_size`.
+    pub fn index(&mut self, start: u32, max_blocks: u32) -> Result<u32, Error> {
+        let start = std::cmp::max(start, self.next_block()?.unwrap_or(0));
+
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
+                Err(e) => return Err(e),
+            };
+            let rpc = rpc::client().await?;
+
+            let decoder = self.events_decoder(client.metadata())?;
+            // runtimes without the utility module cannot have memo transfers
+            let memo_calls = MemoCalls::new(client.metadata()).ok();
+
+            let head = client.finalized_head().await?;
+            let last = match client.header(Some(head)).await? {
+                Some(header) => *header.number(),
+                None => return Err(Error::Other(format!("Block {:?} not found", head))),
+            };
+
+            let mut indexed = 0;
+            for number in block_range(start, last, max_blocks) {
+                let records =
+                    block_records(&client, &rpc, &decoder, memo_calls.as_ref(), number).await?;
+                self.store(number, &records)?;
+                indexed += 1;
+            }
+            Ok(indexed)
+        })
+    }
+
+    /// a decoder for the events of every module of the runtime
+    fn events_decoder(
+        &self,
+        metadata: &Metadata,
+    ) -> Result<EventsDecoder<IndracoreNodeRuntime>, Error> {
+        let mut decoder = EventsDecoder::<IndracoreNodeRuntime>::new(metadata.clone());
+        events::with_runtime_types(&mut decoder);
+        for register in &self.types {
+            register(&mut decoder);
+        }
+        if let Err(missing) = decoder.check_missing_type_sizes() {
+            let mut missing: Vec<String> = missing.into_iter().collect();
+            missing.sort();
+            return Err(Error::Other(format!(
+                "Event types without a registered size: {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(decoder)
+    }
+
+    /// the records matching `query`, oldest first
+    pub fn query(&self, query: &Query) -> Result<Vec<Record>, Error> {
+        let account: &[u8] = query.account.as_ref();
+        let counterparty = query
+            .counterparty
+            .as_ref()
+            .map(|id| AsRef::<[u8]>::as_ref(id));
+        self.select(
+            "(source = ?1 OR dest = ?1)
+                AND (?2 IS NULL OR timestamp >= ?2)
+                AND (?3 IS NULL OR timestamp < ?3)
+                AND (?4 IS NULL OR source = ?4 OR dest = ?4)",
+            params![
+                account,
+                query.from.map(millis),
+                query.to.map(millis),
+                counterparty
+            ],
+        )
+    }
+
+    /// the transfers to `account` that came with `memo`, oldest first
+    pub fn find_memo(&self, account: &AccountId32, memo: &[u8]) -> Result<Vec<Record>, Error> {
+        let account: &[u8] = account.as_ref();
+        self.select(
+            "kind = 'transfer' AND dest = ?1 AND memo = ?2",
+            params![account, memo],
+        )
+    }
+
+    fn select(&self, filter: &str, params: &[&dyn ToSql]) -> Result<Vec<Record>, Error> {
+        let mut stmt = db(self.conn.prepare(&format!(
+            "SELECT block_number, block_hash, timestamp, extrinsic_index,
+                kind, source, dest, amount, memo
+            FROM records
+            WHERE {}
+            ORDER BY block_number, id",
+            filter
+        )))?;
+        let rows = db(stmt.query_map(params, |row| {
+            Ok((
+                (
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, Option<u32>>(3)?,
+                ),
+                (
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<Vec<u8>>>(5)?,
+                    row.get::<_, Option<Vec<u8>>>(6)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, Option<Vec<u8>>>(8)?,
+                ),
+            ))
+        }))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (
+                (block_number, block_hash, timestamp, extrinsic_index),
+                (kind, from, to, amount, memo),
+            ) = db(row)?;
+            records.push(Record {
+                block_number,
+                block_hash: H256::from_slice(&block_hash),
+                timestamp: UNIX_EPOCH + Duration::from_millis(timestamp as u64),
+                extrinsic_index,
+                kind: RecordKind::parse(&kind)?,
+                from: from.map(|id| account_id(&id)).transpose()?,
+                to: to.map(|id| account_id(&id)).transpose()?,
+                amount: match amount.parse::<u128>() {
+                    Ok(amount) => amount.into(),
+                    Err(e) => return Err(Error::Other(format!("{:?}", e))),
+                },
+                memo,
+            });
+        }
+        Ok(records)
+    }
+
+    /// replace the records of block `number` and move past it
+    fn store(&mut self, number: u32, records: &[Record]) -> Result<(), Error> {
+        let tx = db(self.conn.transaction())?;
+        db(tx.execute(
+            "DELETE FROM records WHERE block_number = ?1",
+            params![number],
+        ))?;
+        for record in records {
+            db(tx.execute(
+                "INSERT INTO records
+                (block_number, block_hash, timestamp, extrinsic_index,
+                kind, source, dest, amount, memo)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    record.block_number,
+                    record.block_hash.as_bytes(),
+                    millis(record.timestamp),
+                    record.extrinsic_index,
+                    record.kind.as_str(),
+                    record.from.as_ref().map(|id| AsRef::<[u8]>::as_ref(id)),
+                    record.to.as_ref().map(|id| AsRef::<[u8]>::as_ref(id)),
+                    record.amount.plancks().to_string(),
+                    record.memo,
+                ],
+            ))?;
+        }
+        db(tx.execute(
+            "INSERT OR REPLACE INTO progress (id, next_block) VALUES (0, ?1)",
+            params![number + 1],
+        ))?;
+        db(tx.commit())
+    }
+}
+
+/// The blocks from `start` one run of `index` covers, at most `max_blocks` and
+/// none after the finalized block `last`.
+fn block_range(start: u32, last: u32, max_blocks: u32) -> RangeInclusive<u32> {
+    match max_blocks.checked_sub(1) {
+        Some(more) => start..=cmp::min(last, start.saturating_add(more)),
+        None => 1..=0,
+    }
+}
+
+/// the balance records of block `number`
+async fn block_records(
+    client: &primitives::Client,
+    rpc: &jsonrpsee::Client,
+    decoder: &EventsDecoder<IndracoreNodeRuntime>,
+    memo_calls: Option<&MemoCalls>,
+    number: u32,
+) -> Result<Vec<Record>, Error> {
+    let block_hash = match client.block_hash(Some(number.into())).await? {
+        Some(hash) => hash,
+        None => return Err(Error::Other(format!("Block {} not found", number))),
+    };
+    let block = match client.block(Some(block_hash)).await? {
+        Some(block) => block.block,
+        None => return Err(Error::Other(format!("Block {:?} not found", block_hash))),
+    };
+    let timestamp = match rpc::storage_value(rpc, "Timestamp", "Now", block_hash).await? {
+        Some(data) => u64::decode(&mut &data.0[..])?,
+        None => 0,
+    };
+    let timestamp = UNIX_EPOCH + Duration::from_millis(timestamp);
+
+    let mut records = match rpc::storage_value(rpc, "System", "Events", block_hash).await? {
+        Some(data) => event_records(decoder, &data.0, number, block_hash, timestamp)?,
+        None => Vec::new(),
+    };
+
+    // fees emit no event, ask the node to estimate what each signed extrinsic
+    // was charged
+    let parent = *block.header.parent_hash();
+    let mut memos = HashMap::new();
+    for (index, extrinsic) in block.extrinsics.iter().enumerate() {
+        let encoded = extrinsic.encode();
+        let (signed, call) = decode_extrinsic(&encoded)?;
+        if let Some(memo) = memo_calls.and_then(|calls| calls.decode(call)) {
+            memos.insert(index as u32, memo);
+        }
+        let (signer, tip) = match signed {
+            Some(signed) => signed,
+            None => continue,
+        };
+        let payer = match address_at(client, &signer, parent).await? {
+            Some(payer) => payer,
+            None => {
+                log::warn!("Signer of extrinsic {}-{} not found", number, index);
+                continue;
+            }
+        };
+        let info = rpc::payment_query_info(rpc, &encoded, Some(parent)).await?;
+        records.push(Record {
+            block_number: number,
+            block_hash,
+            timestamp,
+            extrinsic_index: Some(index as u32),
+            kind: RecordKind::Fee,
+            from: Some(payer),
+            to: None,
+            amount: info.partial_fee.saturating_add(tip).into(),
+            memo: None,
+        });
+    }
+
+    for record in records.iter_mut() {
+        if record.kind != RecordKind::Transfer {
+            continue;
+        }
+        let (reciever, amount, memo) = match record.extrinsic_index.and_then(|i| memos.get(&i)) {
+            Some(memo) => memo,
+            None => continue,
+        };
+        if *amount != record.amount.plancks() {
+            continue;
+        }
+        // the memo stays unpaired if its index was not assigned before the block
+        if address_at(client, reciever, parent).await? == record.to {
+            record.memo = Some(memo.clone());
+        }
+    }
+    Ok(records)
+}
+
+/// the transfer and deposit records of the SCALE encoded `System::Events` of a block
+fn event_records(
+    decoder: &EventsDecoder<IndracoreNodeRuntime>,
+    data: &[u8],
+    number: u32,
+    block_hash: H256,
+    timestamp: SystemTime,
+) -> Result<Vec<Record>, Error> {
+    let mut records = Vec::new();
+    for (phase, raw) in decoder.decode_events(&mut &data[..])? {
+        let event = match raw {
+            Raw::Event(event) if event.module == "Balances" => event,
+            _ => continue,
+        };
+        let (kind, from, to, amount) = match event.variant.as_str() {
+            "Transfer" => {
+                let transfer = TransferEvent::<IndracoreNodeRuntime>::decode(&mut &event.data[..])?;
+                (
+                    RecordKind::Transfer,
+                    Some(transfer.from),
+                    Some(transfer.to),
+                    transfer.amount,
+                )
+            }
+            "Deposit" => {
+                let (who, amount) = <(AccountId32, u128)>::decode(&mut &event.data[..])?;
+                (RecordKind::Deposit, None, Some(who), amount)
+            }
+            _ => continue,
+        };
+        records.push(Record {
+            block_number: number,
+            block_hash,
+            timestamp,
+            extrinsic_index: match phase {
+                Phase::ApplyExtrinsic(index) => Some(index),
+                _ => None,
+            },
+            kind,
+            from,
+            to,
+            amount: amount.into(),
+            memo: None,
+        });
+    }
+    Ok(records)
+}
+
+/// The account `address` refers to in the state of block `at`, `None` if it is
+/// an index that was not assigned then. Addresses in an extrinsic are resolved
+/// when it is applied, so `at` is the parent of its block.
+async fn address_at(
+    client: &primitives::Client,
+    address: &primitives::IndracoreId,
+    at: H256,
+) -> Result<Option<AccountId32>, Error> {
+    match address {
+        Address::Id(id) => Ok(Some(id.clone())),
+        Address::Index(index) => indices::lookup_at(client, *index, Some(at)).await,
+    }
+}
+
+/// The signer and tip of the SCALE encoded extrinsic `encoded`, `None` if it is
+/// not signed, and its call.
+fn decode_extrinsic(
+    encoded: &[u8],
+) -> Result<(Option<(primitives::IndracoreId, u128)>, &[u8]), Error> {
+    let input = &mut &encoded[..];
+    let _len = Compact::<u32>::decode(input)?;
+    let version = u8::decode(input)?;
+    if version & 0b1000_0000 == 0 {
+        return Ok((None, input));
+    }
+    let signer = primitives::IndracoreId::decode(input)?;
+    let _signature = MultiSignature::decode(input)?;
+    let _era = Era::decode(input)?;
+    let _nonce = Compact::<u32>::decode(input)?;
+    let tip = Compact::<u128>::decode(input)?;
+    Ok((Some((signer, tip.0)), input))
+}
+
+/// The call indices a memo transfer is made of, read from the runtime metadata.
+struct MemoCalls {
+    batch_all: Vec<u8>,
+    transfer: Vec<u8>,
+    remark: Vec<u8>,
+}
+
+impl MemoCalls {
+    fn new(metadata: &Metadata) -> Result<Self, Error> {
+        // a call without arguments encodes to its module and call index
+        let index = |module: &str, call: &str| -> Result<Vec<u8>, Error> {
+            Ok(metadata.module_with_calls(module)?.call(call, ())?.0)
+        };
+        Ok(MemoCalls {
+            batch_all: index("Utility", "batch_all")?,
+            transfer: index("Balances", "transfer")?,
+            remark: index("System", "remark")?,
+        })
+    }
+
+    /// the reciever, amount and memo of `call` if it is
+    /// `utility.batch_all([balances.transfer, system.remark])`
+    fn decode(&self, call: &[u8]) -> Option<(primitives::IndracoreId, u128, Vec<u8>)> {
+        let input = &mut &call[..];
+        if !strip_prefix(input, &self.batch_all) || Compact::<u32>::decode(input).ok()?.0 != 2 {
+            return None;
+        }
+        if !strip_prefix(input, &self.transfer) {
+            return None;
+        }
+        let reciever = primitives::IndracoreId::decode(input).ok()?;
+        let amount = Compact::<u128>::decode(input).ok()?.0;
+        if !strip_prefix(input, &self.remark) {
+            return None;
+        }
+        let memo = Vec::<u8>::decode(input).ok()?;
+        if !input.is_empty() {
+            return None;
+        }
+        Some((reciever, amount, memo))
+    }
+}
+
+/// advance `input` past `prefix` if it starts with it
+fn strip_prefix(input: &mut &[u8], prefix: &[u8]) -> bool {
+    let rest: &[u8] = *input;
+    if rest.starts_with(prefix) {
+        *input = &rest[prefix.len()..];
+        true
+    } else {
+        false
+    }
+}
+
+fn account_id(bytes: &[u8]) -> Result<AccountId32, Error> {
+    match AccountId32::try_from(bytes) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(Error::Other(format!(
+            "Invalid account id of {} bytes",
+            bytes.len()
+        ))),
+    }
+}
+
+fn millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as i64,
+        Err(_) => 0,
+    }
+}
+
+fn db<T>(result: rusqlite::Result<T>) -> Result<T, Error> {
+    match result {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balance::history::{
+            block_range, decode_extrinsic, event_records, History, MemoCalls, Query, Record,
+            RecordKind,
+        },
+        extrinsic::SigningParams,
+    };
+    use frame_metadata::{
+        DecodeDifferent, EventMetadata, ExtrinsicMetadata, ModuleMetadata, RuntimeMetadata,
+        RuntimeMetadataPrefixed, RuntimeMetadataV12, META_RESERVED,
+    };
+    use pallet_indices::address::Address;
+    use scale::{Compact, Encode};
+    use std::{
+        convert::TryFrom,
+        time::{Duration, UNIX_EPOCH},
+    };
+    use substrate_subxt::{
+        sp_core::{crypto::AccountId32, sr25519, Pair, H256},
+        sp_runtime::generic::Era,
+        Encoded, IndracoreNodeRuntime, Metadata, UncheckedExtrinsic,
+    };
+
+    /// runtime metadata with a module for each of `modules`, named after it and
+    /// with its events and their argument types
+    fn metadata(modules: &[(&str, &[(&str, &[&str])])]) -> Metadata {
+        let modules = modules
+            .iter()
+            .enumerate()
+            .map(|(index, (name, events))| ModuleMetadata {
+                name: DecodeDifferent::Decoded(name.to_string()),
+                storage: None,
+                calls: None,
+                event: Some(DecodeDifferent::Decoded(
+                    events
+                        .iter()
+                        .map(|(event, arguments)| EventMetadata {
+                            name: DecodeDifferent::Decoded(event.to_string()),
+                            arguments: DecodeDifferent::Decoded(
+                                arguments.iter().map(|arg| arg.to_string()).collect(),
+                            ),
+                            documentation: DecodeDifferent::Decoded(Vec::new()),
+                        })
+                        .collect(),
+                )),
+                constants: DecodeDifferent::Decoded(Vec::new()),
+                errors: DecodeDifferent::Decoded(Vec::new()),
+                index: index as u8,
+            })
+            .collect();
+        let metadata = RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(RuntimeMetadataV12 {
+                modules: DecodeDifferent::Decoded(modules),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: Vec::new(),
+                },
+            }),
+        );
+        Metadata::try_from(metadata).unwrap()
+    }
+
+    #[test]
+    fn test_block_range() {
+        assert_eq!(block_range(0, 1_000, 100), 0..=99);
+        assert_eq!(block_range(950, 1_000, 100), 950..=1_000);
+        assert_eq!(block_range(1_001, 1_000, 100).count(), 0);
+        assert_eq!(block_range(0, 1_000, 0).count(), 0);
+        assert_eq!(
+            block_range(u32::max_value(), u32::max_value(), 5).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_foreign_events() {
+        let metadata = metadata(&[
+            ("Session", &[("NewSession", &["SessionIndex"])]),
+            ("Grandpa", &[("NewAuthorities", &["AuthorityList"])]),
+            (
+                "ImOnline",
+                &[("SomeOffline", &["Vec<IdentificationTuple>"])],
+            ),
+            (
+                "Balances",
+                &[("Transfer", &["AccountId", "AccountId", "Balance"])],
+            ),
+        ]);
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+
+        // every event applied by extrinsic 1, without topics
+        let mut data = Compact(4u32).encode();
+        let mut event = |module: u8, args: Vec<u8>| {
+            data.push(0);
+            1u32.encode_to(&mut data);
+            data.extend_from_slice(&[module, 0]);
+            data.extend(args);
+            Compact(0u32).encode_to(&mut data);
+        };
+        event(0, 7u32.encode());
+        event(1, vec![([9u8; 32], 1u64)].encode());
+        // no validator offline
+        event(2, Compact(0u32).encode());
+        event(3, (alice.clone(), bob.clone(), 500u128).encode());
+
+        let mut history = History::open(":memory:").unwrap();
+        let decoder = history.events_decoder(&metadata).unwrap();
+        let records = event_records(&decoder, &data, 5, H256::zero(), UNIX_EPOCH).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, RecordKind::Transfer);
+        assert_eq!(records[0].from, Some(alice));
+        assert_eq!(records[0].to, Some(bob));
+        assert_eq!(records[0].extrinsic_index, Some(1));
+        assert_eq!(records[0].amount, 500.into());
+
+        // a module of a newer runtime is refused before indexing, until its
+        // types are registered
+        let foreign = metadata(&[("Foreign", &[("Happened", &["Mystery"])])]);
+        assert!(history.events_decoder(&foreign).is_err());
+        history.register_type_size::<[u8; 4]>("Mystery");
+        assert!(history.events_decoder(&foreign).is_ok());
+    }
+
+    #[test]
+    fn test_decode_extrinsic() {
+        let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+        let params = SigningParams {
+            spec_version: 1,
+            transaction_version: 1,
+            genesis_hash: Default::default(),
+            era: Era::Immortal,
+            block_hash: Default::default(),
+            nonce: 3,
+            tip: 7,
+        };
+        let extrinsic = UncheckedExtrinsic::<IndracoreNodeRuntime>::new_signed(
+            Encoded(vec![0, 1, 0]),
+            AccountId32::from(alice.public()).into(),
+            alice.sign(b"payload").into(),
+            params.extra(),
+        );
+
+        let encoded = extrinsic.encode();
+        let (signed, call) = decode_extrinsic(&encoded).unwrap();
+        let (signer, tip) = signed.unwrap();
+        assert_eq!(signer, Address::Id(alice.public().into()));
+        assert_eq!(tip, 7);
+        assert_eq!(call, &[0, 1, 0]);
+
+        let unsigned =
+            UncheckedExtrinsic::<IndracoreNodeRuntime>::new_unsigned(Encoded(vec![0, 1, 0]));
+        let encoded = unsigned.encode();
+        let (signed, call) = decode_extrinsic(&encoded).unwrap();
+        assert!(signed.is_none());
+        assert_eq!(call, &[0, 1, 0]);
+    }
+
+    #[test]
+    fn test_memo_calls() {
+        let calls = MemoCalls {
+            batch_all: vec![1, 2],
+            transfer: vec![4, 0],
+            remark: vec![0, 1],
+        };
+        let bob = AccountId32::from([2; 32]);
+        let transfer = |to: &AccountId32, amount: u128| {
+            let mut call = vec![4, 0];
+            Address::<AccountId32, u32>::Id(to.clone()).encode_to(&mut call);
+            Compact(amount).encode_to(&mut call);
+            call
+        };
+        let mut remark = vec![0, 1];
+        b"INV-42".to_vec().encode_to(&mut remark);
+
+        let mut batch = vec![1, 2];
+        vec![Encoded(transfer(&bob, 500)), Encoded(remark.clone())].encode_to(&mut batch);
+        assert_eq!(
+            calls.decode(&batch),
+            Some((Address::Id(bob.clone()), 500, b"INV-42".to_vec()))
+        );
+
+        // a plain transfer, or a batch with other calls, has no memo
+        assert_eq!(calls.decode(&transfer(&bob, 500)), None);
+        let mut batch = vec![1, 2];
+        vec![Encoded(remark.clone()), Encoded(transfer(&bob, 500))].encode_to(&mut batch);
+        assert_eq!(calls.decode(&batch), None);
+    }
+
+    #[test]
+    fn test_store_and_query() {
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+        let charlie = AccountId32::from([3; 32]);
+        let record =
+            |number: u32, kind, from: Option<&AccountId32>, to: Option<&AccountId32>| Record {
+                block_number: number,
+                block_hash: Default::default(),
+                timestamp: UNIX_EPOCH + Duration::from_secs(number as u64 * 6),
+                extrinsic_index: Some(1),
+                kind,
+                from: from.cloned(),
+                to: to.cloned(),
+                amount: 1_000.into(),
+                memo: None,
+            };
+
+        let mut history = History::open(":memory:").unwrap();
+        assert_eq!(history.next_block().unwrap(), None);
+        let first = vec![
+            record(1, RecordKind::Transfer, Some(&alice), Some(&bob)),
+            record(1, RecordKind::Fee, Some(&alice), None),
+        ];
+        let mut second = vec![record(
+            2,
+            RecordKind::Transfer,
+            Some(&charlie),
+            Some(&alice),
+        )];
+        second[0].memo = Some(b"INV-42".to_vec());
+        history.store(1, &first).unwrap();
+        history.store(2, &second).unwrap();
+        // indexing a block again replaces its records
+        history.store(2, &second).unwrap();
+        assert_eq!(history.next_block().unwrap(), Some(3));
+
+        let all = history.query(&Query::new(alice.clone())).unwrap();
+        assert_eq!(
+            all,
+            vec![first[0].clone(), first[1].clone(), second[0].clone()]
+        );
+
+        let with_bob = Query {
+            counterparty: Some(bob.clone()),
+            ..Query::new(alice.clone())
+        };
+        assert_eq!(history.query(&with_bob).unwrap(), vec![first[0].clone()]);
+        assert_eq!(history.find_memo(&alice, b"INV-42").unwrap(), second);
+        assert!(history.find_memo(&bob, b"INV-42").unwrap().is_empty());
+
+        let later = Query {
+            from: Some(UNIX_EPOCH + Duration::from_secs(12)),
+            ..Query::new(alice)
+        };
+        assert_eq!(history.query(&later).unwrap(), second);
+        assert_eq!(history.query(&Query::new(bob)).unwrap().len(), 1);
+    }
+}
@@ -1,2 +1,8 @@
+pub mod batch;
 pub mod check_balance;
+pub mod history;
+pub mod payout;
+pub mod subscribe;
 pub mod transaction;
+pub mod units;
+pub mod validate;
@@ -0,0 +1,729 @@
+use pallet_indices::address::Address;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+use substrate_subxt::{
+    balances::{BalancesEventsDecoder, TransferCall},
+    sp_core::{crypto::Ss58Codec, Encode, H256},
+    sp_runtime::traits::{BlakeTwo256, Hash},
+    system::AccountStoreExt,
+    ClientBuilder, Encoded, Error, IndracoreNodeRuntime, RawEvent, Signer,
+};
+
+use crate::{
+    balance::{
+        batch::{self, ItemStatus},
+        check_balance,
+        units::Balance,
+        validate,
+    },
+    extrinsic::{self, TxOptions},
+    fee,
+    frame::utility::{BatchAllCall, BatchCall},
+    keyring::{self, Sr25519},
+    primitives, rpc,
+    signer::ToSigner,
+};
+
+/// Blocks a payout extrinsic stays valid for. Rows of an extrinsic whose outcome
+/// was lost in a crash are only paid again once it can no longer be included.
+const MORTALITY: u64 = 64;
+
+/// A row of a payout CSV, `line` is the line in the file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayoutRow {
+    pub line: usize,
+    pub reciever: primitives::IndracoreId,
+    pub amount: Balance,
+    /// only kept locally and copied to the `PayoutItem` of the row, it is not
+    /// sent on chain nor written to the journal
+    pub memo: String,
+}
+
+/// A row of a payout CSV that cannot be paid.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct CsvRow {
+    address: String,
+    amount: String,
+    #[serde(default)]
+    memo: String,
+}
+
+/// Read and validate a CSV with `address`, `amount` and `memo` columns. Amounts
+/// are parsed like `Balance`, e.g. `12.5` or `250 mSEL`.
+pub fn read_csv<R: Read>(reader: R) -> Result<(Vec<PayoutRow>, Vec<InvalidRow>), Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = csv_result(reader.headers())?.clone();
+
+    let mut rows = Vec::new();
+    let mut invalid = Vec::new();
+    for record in reader.records() {
+        let record = csv_result(record)?;
+        let line = record.position().map_or(0, |pos| pos.line() as usize);
+        let row = match record.deserialize::<CsvRow>(Some(&headers)) {
+            Ok(row) => row,
+            Err(e) => {
+                invalid.push(InvalidRow {
+                    line,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        match parse_row(line, row) {
+            Ok(row) => rows.push(row),
+            Err(reason) => invalid.push(InvalidRow { line, reason }),
+        }
+    }
+    Ok((rows, invalid))
+}
+
+fn parse_row(line: usize, row: CsvRow) -> Result<PayoutRow, String> {
+    if let Some(problem) = validate::check_address(&row.address).into_iter().next() {
+        return Err(problem.to_string());
+    }
+    let reciever = match keyring::indracoreid(&row.address) {
+        Ok(id) => id,
+        Err(e) => return Err(format!("{:?}", e)),
+    };
+    let amount = match row.amount.parse::<Balance>() {
+        Ok(amount) if amount.plancks() > 0 => amount,
+        Ok(_) => return Err("Amount is zero".into()),
+        Err(e) => return Err(format!("{:?}", e)),
+    };
+    Ok(PayoutRow {
+        line,
+        reciever,
+        amount,
+        memo: row.memo,
+    })
+}
+
+/// Pays every row from one sender in `utility.batch` extrinsics, recording its
+/// progress in a journal so an interrupted payout can be resumed. The memos of
+/// the rows are not part of the extrinsics, use `Transaction::run_with_memo` to
+/// record a memo on chain.
+pub struct Payout<S: ToSigner = Sr25519> {
+    pub sender: S,
+    pub rows: Vec<PayoutRow>,
+    /// run again with the same journal to resume
+    pub journal: PathBuf,
+    /// use `batch_all`, so a failing transfer reverts the rest of its chunk
+    pub atomic: bool,
+}
+
+/// The outcome of one payout row.
+#[derive(Clone, Debug)]
+pub struct PayoutItem {
+    pub line: usize,
+    pub reciever: primitives::IndracoreId,
+    pub amount: Balance,
+    pub memo: String,
+    pub status: ItemStatus,
+    pub extrinsic_hash: Option<H256>,
+    pub block_hash: Option<H256>,
+}
+
+impl<S: ToSigner> Payout<S> {
+    /// Pay every row the journal does not record as paid or pending.
+    ///
+    /// Stops at the first extrinsic that is not included. Running again first
+    /// looks up what happened to it, and waits until it expired if it is nowhere
+    /// to be found, so no row is paid twice. `Failed` and `NotExecuted` rows are
+    /// retried. Nothing is paid if the spendable balance of the sender does not
+    /// cover the rows left and their fees.
+    pub fn run(&self, pass: Option<&str>) -> Result<Vec<PayoutItem>, Error> {
+        let sender = self.sender.to_signer(pass)?;
+        let mut journal = Journal::open(&self.journal)?;
+        journal.check(&self.rows)?;
+
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
+                Err(e) => return Err(e),
+            };
+
+            self.resume(&client, &mut journal).await?;
+
+            let states = journal.rows();
+            let todo: Vec<&PayoutRow> = self
+                .rows
+                .iter()
+                .filter(|row| match states.get(&row.line) {
+                    Some(state) => state.status != Some(ItemStatus::Completed),
+                    None => true,
+                })
+                .collect();
+            if let Some(row) = todo.first() {
+                let item = fee::estimate(TransferCall {
+                    to: &row.reciever,
+                    amount: row.amount.plancks(),
+                })
+                .await?;
+                let size = batch::chunk_size(item.weight, batch::maximum_block_weight(&client));
+                let chunks: Vec<&[&PayoutRow]> = todo.chunks(size).collect();
+                self.check_balance(&client, &sender, &chunks).await?;
+                for chunk in chunks {
+                    self.pay(&client, &sender, &mut journal, chunk).await?;
+                }
+            }
+            Ok(self.report(&journal))
+        })
+    }
+
+    /// the rows `run` can still pay, rows of a pending extrinsic excluded
+    pub(crate) fn unpaid(&self) -> Result<Vec<&PayoutRow>, Error> {
+        let journal = Journal::open(&self.journal)?;
+        journal.check(&self.rows)?;
+        let states = journal.rows();
+        Ok(self
+            .rows
+            .iter()
+            .filter(|row| match states.get(&row.line) {
+                Some(state) => match &state.status {
+                    Some(status) => *status != ItemStatus::Completed,
+                    None => false,
+                },
+                None => true,
+            })
+            .collect())
+    }
+
+    /// fail before paying anything if the sender cannot pay for every chunk, the
+    /// fee of the first and largest chunk is counted for each
+    async fn check_balance(
+        &self,
+        client: &primitives::Client,
+        sender: &S::Signer,
+        chunks: &[&[&PayoutRow]],
+    ) -> Result<(), Error> {
+        // the first chunk is the largest, its fee covers each of the others
+        let calls = transfer_calls(client, chunks[0])?;
+        let options = TxOptions {
+            mortality: Some(MORTALITY),
+            ..Default::default()
+        };
+        let fee = if self.atomic {
+            let call = BatchAllCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            fee::estimate_signed(client, call, sender, &options).await?
+        } else {
+            let call = BatchCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            fee::estimate_signed(client, call, sender, &options).await?
+        };
+        let amount = chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .fold(0u128, |sum, row| sum.saturating_add(row.amount.plancks()));
+        let fees = fee::with_margin(fee.partial_fee).saturating_mul(chunks.len() as u128);
+        let required = amount.saturating_add(fees);
+
+        // the transfers may reap the sender, the last chunk would then fail
+        // after its fee was paid, so the existential deposit is kept back
+        let info = client.account(sender.account_id(), None).await?;
+        let frozen = cmp::max(info.data.misc_frozen, info.data.fee_frozen);
+        let existential_deposit = check_balance::existential_deposit_of(client)?;
+        let spendable = check_balance::transferable(info.data.free, frozen, existential_deposit);
+        if required > spendable {
+            let problem = validate::Problem::InsufficientBalance {
+                spendable: spendable.into(),
+                required: required.into(),
+            };
+            return Err(Error::Other(problem.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn pay(
+        &self,
+        client: &primitives::Client,
+        sender: &S::Signer,
+        journal: &mut Journal,
+        chunk: &[&PayoutRow],
+    ) -> Result<(), Error> {
+        let calls = transfer_calls(client, chunk)?;
+
+        let options = TxOptions {
+            mortality: Some(MORTALITY),
+            ..Default::default()
+        };
+        let params = extrinsic::signing_params(client, sender.account_id(), None, &options).await?;
+        let birth = extrinsic::block_number(client, params.block_hash).await?;
+        let death = params.era.death(birth as u64) as u32;
+
+        let (extrinsic, mut decoder) = if self.atomic {
+            let call = BatchAllCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            (
+                extrinsic::sign(client, call, sender, &params).await?,
+                client.events_decoder::<BatchAllCall<IndracoreNodeRuntime>>(),
+            )
+        } else {
+            let call = BatchCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
+            };
+            (
+                extrinsic::sign(client, call, sender, &params).await?,
+                client.events_decoder::<BatchCall<IndracoreNodeRuntime>>(),
+            )
+        };
+        decoder.with_balances();
+        let encoded = extrinsic.encode();
+        let extrinsic_hash = BlakeTwo256::hash(&encoded);
+
+        // recorded before submitting, a crash from here on is resolved by `resume`
+        journal.append(Entry::Submitted {
+            extrinsic_hash,
+            birth,
+            death,
+            atomic: self.atomic,
+            rows: chunk.iter().map(|row| RowRef::new(row)).collect(),
+        })?;
+        let (block_hash, _, index) =
+            extrinsic::submit_and_wait_encoded(client, encoded, &options).await?;
+        let rpc = rpc::client().await?;
+        let events = extrinsic::extrinsic_events(&rpc, &decoder, block_hash, index).await;
+        journal.append(Entry::Included {
+            extrinsic_hash,
+            block_hash,
+            statuses: chunk_statuses(chunk.len(), events)?,
+        })
+    }
+
+    /// settle the extrinsics the journal has no outcome for
+    async fn resume(
+        &self,
+        client: &primitives::Client,
+        journal: &mut Journal,
+    ) -> Result<(), Error> {
+        let pending = journal.pending();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let rpc = rpc::client().await?;
+        let finalized = extrinsic::block_number(client, client.finalized_head().await?).await?;
+
+        for (extrinsic_hash, birth, death, len, atomic) in pending {
+            let mut found = None;
+            for number in birth..=cmp::min(finalized, death.saturating_sub(1)) {
+                let block_hash = match client.block_hash(Some(number.into())).await? {
+                    Some(hash) => hash,
+                    None => return Err(Error::Other(format!("Block {} not found", number))),
+                };
+                if let Some(index) =
+                    extrinsic::find_extrinsic(client, block_hash, extrinsic_hash).await?
+                {
+                    found = Some((block_hash, index));
+                    break;
+                }
+            }
+
+            match found {
+                Some((block_hash, index)) => {
+                    let mut decoder = if atomic {
+                        client.events_decoder::<BatchAllCall<IndracoreNodeRuntime>>()
+                    } else {
+                        client.events_decoder::<BatchCall<IndracoreNodeRuntime>>()
+                    };
+                    decoder.with_balances();
+                    let events =
+                        extrinsic::extrinsic_events(&rpc, &decoder, block_hash, index).await;
+                    journal.append(Entry::Included {
+                        extrinsic_hash,
+                        block_hash,
+                        statuses: chunk_statuses(len, events)?,
+                    })?;
+                }
+                None if finalized + 1 >= death => {
+                    journal.append(Entry::Expired { extrinsic_hash })?
+                }
+                None => {
+                    return Err(Error::Other(format!(
+                        "Extrinsic {:?} may be included until block {}, resume after it",
+                        extrinsic_hash, death
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn report(&self, journal: &Journal) -> Vec<PayoutItem> {
+        let states = journal.rows();
+        self.rows
+            .iter()
+            .map(|row| {
+                let state = states.get(&row.line);
+                PayoutItem {
+                    line: row.line,
+                    reciever: row.reciever.clone(),
+                    amount: row.amount,
+                    memo: row.memo.clone(),
+                    status: state
+                        .and_then(|state| state.status.clone())
+                        .unwrap_or(ItemStatus::NotExecuted),
+                    extrinsic_hash: state.map(|state| state.extrinsic_hash),
+                    block_hash: state.and_then(|state| state.block_hash),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The row a journal entry refers to, to notice a CSV edited between runs.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct RowRef {
+    line: usize,
+    reciever: String,
+    amount: u128,
+}
+
+impl RowRef {
+    fn new(row: &PayoutRow) -> Self {
+        RowRef {
+            line: row.line,
+            reciever: match &row.reciever {
+                Address::Id(id) => id.to_ss58check(),
+                Address::Index(index) => index.to_string(),
+            },
+            amount: row.amount.plancks(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "entry", rename_all = "camelCase")]
+enum Entry {
+    /// an extrinsic about to be submitted, valid in blocks `birth..death`
+    #[serde(rename_all = "camelCase")]
+    Submitted {
+        extrinsic_hash: H256,
+        birth: u32,
+        death: u32,
+        /// a `batch_all` rather than a `batch`
+        #[serde(default)]
+        atomic: bool,
+        rows: Vec<RowRef>,
+    },
+    /// the outcome of a submitted extrinsic, one status per row
+    #[serde(rename_all = "camelCase")]
+    Included {
+        extrinsic_hash: H256,
+        block_hash: H256,
+        statuses: Vec<ItemStatus>,
+    },
+    /// a submitted extrinsic that can no longer be included
+    #[serde(rename_all = "camelCase")]
+    Expired { extrinsic_hash: H256 },
+}
+
+/// The latest state of a row, `status` is `None` while its extrinsic is pending.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RowState {
+    status: Option<ItemStatus>,
+    extrinsic_hash: H256,
+    block_hash: Option<H256>,
+}
+
+/// Append-only payout journal with one JSON entry per line. Every entry is
+/// synced to disk before the payout goes on.
+struct Journal {
+    file: File,
+    entries: Vec<Entry>,
+}
+
+impl Journal {
+    fn open(path: &Path) -> Result<Self, Error> {
+        let mut content = String::new();
+        if path.exists() {
+            io_result(File::open(path).and_then(|mut file| file.read_to_string(&mut content)))?;
+        }
+        let mut file = io_result(OpenOptions::new().create(true).append(true).open(path))?;
+
+        let mut entries = Vec::new();
+        let mut valid = 0;
+        let mut lines = content.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            let last = lines.peek().is_none();
+            if line.trim().is_empty() {
+                valid += line.len() + 1;
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                // a crash while appending leaves a torn last line
+                Err(_) if last => {
+                    io_result(file.set_len(valid as u64))?;
+                    break;
+                }
+                Err(e) => return Err(Error::Other(format!("Invalid journal entry: {:?}", e))),
+            }
+            if last {
+                io_result(file.write_all(b"\n"))?;
+            }
+            valid += line.len() + 1;
+        }
+        Ok(Journal { file, entries })
+    }
+
+    fn append(&mut self, entry: Entry) -> Result<(), Error> {
+        let mut line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => return Err(Error::Other(format!("{:?}", e))),
+        };
+        line.push('\n');
+        io_result(self.file.write_all(line.as_bytes()))?;
+        io_result(self.file.sync_all())?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// fail if a row the journal refers to is not the same in `rows`
+    fn check(&self, rows: &[PayoutRow]) -> Result<(), Error> {
+        let rows: HashMap<usize, RowRef> = rows
+            .iter()
+            .map(|row| (row.line, RowRef::new(row)))
+            .collect();
+        for entry in &self.entries {
+            if let Entry::Submitted { rows: refs, .. } = entry {
+                for row in refs {
+                    if rows.get(&row.line) != Some(row) {
+                        return Err(Error::Other(format!(
+                            "Row on line {} changed since it was submitted",
+                            row.line
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `(extrinsic_hash, birth, death, rows)` of the extrinsics without outcome
+    fn pending(&self) -> Vec<(H256, u32, u32, usize, bool)> {
+        let mut pending = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                Entry::Submitted {
+                    extrinsic_hash,
+                    birth,
+                    death,
+                    atomic,
+                    rows,
+                } => pending.push((*extrinsic_hash, *birth, *death, rows.len(), *atomic)),
+                Entry::Included { extrinsic_hash, .. } | Entry::Expired { extrinsic_hash } => {
+                    pending.retain(|(hash, ..)| hash != extrinsic_hash)
+                }
+            }
+        }
+        pending
+    }
+
+    fn rows(&self) -> HashMap<usize, RowState> {
+        let mut lines: HashMap<H256, Vec<usize>> = HashMap::new();
+        let mut states = HashMap::new();
+        for entry in &self.entries {
+            match entry {
+                Entry::Submitted {
+                    extrinsic_hash,
+                    rows,
+                    ..
+                } => {
+                    for row in rows {
+                        states.insert(
+                            row.line,
+                            RowState {
+                                status: None,
+                                extrinsic_hash: *extrinsic_hash,
+                                block_hash: None,
+                            },
+                        );
+                    }
+                    lines.insert(*extrinsic_hash, rows.iter().map(|row| row.line).collect());
+                }
+                Entry::Included {
+                    extrinsic_hash,
+                    block_hash,
+                    statuses,
+                } => {
+                    let chunk = lines.get(extrinsic_hash).cloned().unwrap_or_default();
+                    for (line, status) in chunk.into_iter().zip(statuses) {
+                        states.insert(
+                            line,
+                            RowState {
+                                status: Some(status.clone()),
+                                extrinsic_hash: *extrinsic_hash,
+                                block_hash: Some(*block_hash),
+                            },
+                        );
+                    }
+                }
+                Entry::Expired { extrinsic_hash } => {
+                    for line in lines.get(extrinsic_hash).cloned().unwrap_or_default() {
+                        states.remove(&line);
+                    }
+                }
+            }
+        }
+        states
+    }
+}
+
+/// The row statuses of an included chunk of `len` rows from the events of its
+/// extrinsic, or from the error of a failed `batch_all`.
+fn chunk_statuses(
+    len: usize,
+    events: Result<Vec<RawEvent>, Error>,
+) -> Result<Vec<ItemStatus>, Error> {
+    match events {
+        Ok(events) => Ok(batch::item_statuses(len, &events)),
+        // the batch_all failed, none of its transfers happened
+        Err(Error::Runtime(e)) => Ok(vec![ItemStatus::Failed(format!("{:?}", e)); len]),
+        Err(e) => Err(e),
+    }
+}
+
+fn transfer_calls(
+    client: &primitives::Client,
+    chunk: &[&PayoutRow],
+) -> Result<Vec<Encoded>, Error> {
+    let mut calls = Vec::with_capacity(chunk.len());
+    for row in chunk {
+        calls.push(client.encode(TransferCall {
+            to: &row.reciever,
+            amount: row.amount.plancks(),
+        })?);
+    }
+    Ok(calls)
+}
+
+fn csv_result<T>(result: csv::Result<T>) -> Result<T, Error> {
+    match result {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+fn io_result<T>(result: io::Result<T>) -> Result<T, Error> {
+    match result {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::{
+        batch::ItemStatus,
+        payout::{read_csv, Entry, Journal, RowRef},
+    };
+    use std::io::Write;
+    use substrate_subxt::sp_core::H256;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    #[test]
+    fn test_read_csv() {
+        let csv = format!(
+            "address,amount,memo\n{},1000 planck,march\n42, 5 planck ,\n\
+             not-an-address,1 planck,x\n{},0,zero\n",
+            ALICE, ALICE
+        );
+        let (rows, invalid) = read_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].amount.plancks(), 1_000);
+        assert_eq!(rows[0].memo, "march");
+        assert_eq!(rows[1].amount.plancks(), 5);
+        assert_eq!(
+            invalid.iter().map(|row| row.line).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+    }
+
+    #[test]
+    fn test_journal_resume() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("indracore-payout-{}.jsonl", std::process::id()));
+        let csv = format!(
+            "address,amount,memo\n{},1000 planck,\n42,5 planck,\n",
+            ALICE
+        );
+        let (rows, _) = read_csv(csv.as_bytes()).unwrap();
+
+        let first = H256::repeat_byte(1);
+        let second = H256::repeat_byte(2);
+        let mut journal = Journal::open(&path).unwrap();
+        journal
+            .append(Entry::Submitted {
+                extrinsic_hash: first,
+                birth: 10,
+                death: 74,
+                atomic: false,
+                rows: vec![RowRef::new(&rows[0])],
+            })
+            .unwrap();
+        journal
+            .append(Entry::Included {
+                extrinsic_hash: first,
+                block_hash: H256::repeat_byte(9),
+                statuses: vec![ItemStatus::Completed],
+            })
+            .unwrap();
+        journal
+            .append(Entry::Submitted {
+                extrinsic_hash: second,
+                birth: 11,
+                death: 75,
+                atomic: true,
+                rows: vec![RowRef::new(&rows[1])],
+            })
+            .unwrap();
+        // a crash in the middle of the next entry
+        journal.file.write_all(b"{\"entry\":\"incl").unwrap();
+        drop(journal);
+
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(journal.entries.len(), 3);
+        assert_eq!(journal.pending(), vec![(second, 11, 75, 1, true)]);
+        let states = journal.rows();
+        assert_eq!(states[&2].status, Some(ItemStatus::Completed));
+        assert_eq!(states[&3].status, None);
+        assert!(journal.check(&rows).is_ok());
+
+        let csv = format!(
+            "address,amount,memo\n{},2000 planck,\n42,5 planck,\n",
+            ALICE
+        );
+        let (changed, _) = read_csv(csv.as_bytes()).unwrap();
+        assert!(journal.check(&changed).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    balance::check_balance::{self, BalanceInfo},
+    primitives, rpc,
+};
+use async_std::future::timeout;
+use jsonrpsee::{client::Subscription, common::Params};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+use substrate_subxt::{
+    sp_core::{
+        crypto::AccountId32,
+        storage::{StorageChangeSet, StorageData, StorageKey},
+        Decode, H256,
+    },
+    system::{AccountInfo, AccountStore},
+    ClientBuilder, Error, IndracoreNodeRuntime, Store,
+};
+
+/// How long `BalanceSubscription::next` waits for a change before it checks that
+/// the connection is still open.
+const KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Yields the balance of watched accounts every time their `System::Account` entry
+/// changes, starting with the current balance of each. All accounts share one
+/// storage subscription, on a connection of its own next to the client that
+/// reads the locks and vesting of changed accounts.
+pub struct BalanceSubscription {
+    client: primitives::Client,
+    accounts: HashMap<StorageKey, AccountId32>,
+    sub: Subscription<StorageChangeSet<H256>>,
+    pending: VecDeque<Result<(AccountId32, BalanceInfo), Error>>,
+    closed: bool,
+    rpc: jsonrpsee::Client,
+}
+
+impl BalanceSubscription {
+    /// wait for the next balance change of any watched account, `None` once the
+    /// connection is closed
+    ///
+    /// A change that cannot be decoded is returned as an error without dropping
+    /// the other changes of the same block, the subscription goes on after it.
+    pub async fn next(&mut self) -> Option<Result<(AccountId32, BalanceInfo), Error>> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Some(change);
+            }
+            if self.closed {
+                return None;
+            }
+
+            let set = match timeout(KEEPALIVE, self.sub.next()).await {
+                Ok(set) => set,
+                // the subscription waits forever on a closed connection
+                Err(_) => {
+                    let health: Result<serde_json::Value, Error> =
+                        rpc::request(&self.rpc, "system_health", vec![]).await;
+                    self.closed = health.is_err();
+                    continue;
+                }
+            };
+            for (key, data) in set.changes {
+                let id = match self.accounts.get(&key) {
+                    Some(id) => id.clone(),
+                    None => continue,
+                };
+                let balance = self.balance(&id, data, set.block).await;
+                self.pending.push_back(balance.map(|balance| (id, balance)));
+            }
+        }
+    }
+
+    async fn balance(
+        &self,
+        id: &AccountId32,
+        data: Option<StorageData>,
+        block: H256,
+    ) -> Result<BalanceInfo, Error> {
+        // a reaped account has no entry left
+        let info = match data {
+            Some(data) => AccountInfo::<IndracoreNodeRuntime>::decode(&mut &data.0[..])?,
+            None => Default::default(),
+        };
+        check_balance::balance_info_at(&self.client, id, info, Some(block)).await
+    }
+}
+
+/// watch the balances of `ids`
+pub async fn subscribe_balances(ids: Vec<AccountId32>) -> Result<BalanceSubscription, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+
+    let mut accounts = HashMap::new();
+    for id in ids {
+        let key =
+            AccountStore::<IndracoreNodeRuntime> { account_id: &id }.key(client.metadata())?;
+        accounts.insert(key, id);
+    }
+    let keys: Vec<&StorageKey> = accounts.keys().collect();
+
+    let rpc = rpc::client().await?;
+    let params = Params::Array(vec![rpc::json(keys)?]);
+    let sub = match rpc
+        .subscribe("state_subscribeStorage", params, "state_unsubscribeStorage")
+        .await
+    {
+        Ok(sub) => sub,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(BalanceSubscription {
+        client,
+        accounts,
+        sub,
+        pending: VecDeque::new(),
+        closed: false,
+        rpc,
+    })
+}
@@ -1,23 +1,140 @@
+use std::time::Duration;
 use substrate_subxt::{
-    balances::*, sp_core, sp_core::Decode, ClientBuilder, Error, EventSubscription, EventsDecoder,
-    IndracoreNodeRuntime,
+    balances::*,
+    sp_core,
+    sp_core::{crypto::AccountId32, Decode, Encode},
+    sp_runtime::{traits::Header, DispatchResult},
+    sudo::SudoCall,
+    system::AccountStoreExt,
+    Call, ClientBuilder, Error, ExtrinsicSuccess, IndracoreNodeRuntime, RawEvent, Signer,
 };
 
-use crate::{keyring::Sr25519, primitives};
+use crate::{
+    balance::{check_balance, units::Balance},
+    extrinsic::{self, Included, TxOptions},
+    fee::{self, FeeEstimate},
+    frame::{
+        balances::{ForceTransferCall, TransferKeepAliveCall},
+        system::RemarkCall,
+        utility::BatchAllCall,
+    },
+    keyring::Sr25519,
+    nonce::NonceManager,
+    offline::{self, OfflineParams},
+    primitives, rpc,
+    signer::ToSigner,
+};
 
-pub struct Transaction {
-    pub sender: Sr25519,
+pub struct Transaction<S: ToSigner = Sr25519> {
+    pub sender: S,
     pub reciever: primitives::IndracoreId,
-    pub amount: u128,
+    pub amount: Balance,
 }
 
-impl Transaction {
-    pub fn run(&self, pass: Option<&str>) -> Result<sp_core::H256, Error> {
-        let sender = match self.sender.pair(pass) {
-            Ok(pair) => pair,
-            Err(e) => return Err(e),
+/// The outcome of a transfer once it is included in a block.
+#[derive(Debug)]
+pub struct TransferReceipt {
+    pub block_hash: sp_core::H256,
+    pub block_number: u32,
+    pub extrinsic_hash: sp_core::H256,
+    pub extrinsic_index: u32,
+    pub transfer: TransferEvent<IndracoreNodeRuntime>,
+    /// fee for the extrinsic weight and length as `payment_queryInfo` estimates
+    /// it at the parent block, tip included; weight refunded after dispatch is
+    /// not deducted, so the fee charged can be lower
+    pub estimated_fee: u128,
+    /// every other event emitted by the extrinsic
+    pub events: Vec<RawEvent>,
+}
+
+impl<S: ToSigner> Transaction<S> {
+    /// estimate the fee of the transfer without signing it
+    pub fn estimate_fee(&self) -> Result<FeeEstimate, Error> {
+        async_std::task::block_on(fee::estimate(TransferCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        }))
+    }
+
+    /// sign the transfer without a connection to the node, see `offline::sign`
+    pub fn sign_offline(
+        &self,
+        pass: Option<&str>,
+        params: &OfflineParams,
+    ) -> Result<String, Error> {
+        let sender = self.sender.to_signer(pass)?;
+        let call = TransferCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        };
+        offline::sign(call, &sender, params)
+    }
+
+    pub fn run(&self, pass: Option<&str>) -> Result<TransferReceipt, Error> {
+        self.run_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `run`, but give up if the transfer is not included within `wait`
+    pub fn run_with_timeout(
+        &self,
+        pass: Option<&str>,
+        wait: Duration,
+    ) -> Result<TransferReceipt, Error> {
+        let call = TransferCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        };
+        let options = TxOptions {
+            timeout: wait,
+            ..Default::default()
         };
+        self.submit(pass, call, self.amount.plancks(), &options)
+    }
+
+    /// like `run`, with the era, tip, nonce and wait condition of `options`
+    pub fn run_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let call = TransferCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        };
+        self.submit(pass, call, self.amount.plancks(), options)
+    }
 
+    /// like `run`, but fail instead of reaping the sender account
+    pub fn run_keep_alive(&self, pass: Option<&str>) -> Result<TransferReceipt, Error> {
+        self.run_keep_alive_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `run_keep_alive`, with the era, tip, nonce and wait condition of `options`
+    pub fn run_keep_alive_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let call = TransferKeepAliveCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        };
+        self.submit(pass, call, self.amount.plancks(), options)
+    }
+
+    /// like `run`, with `memo` stored next to the transfer in a `system.remark`,
+    /// both in one `utility.batch_all` so neither happens without the other
+    pub fn run_with_memo(&self, pass: Option<&str>, memo: &[u8]) -> Result<TransferReceipt, Error> {
+        self.run_with_memo_with_options(pass, memo, &TxOptions::default())
+    }
+
+    /// like `run_with_memo`, with the era, tip, nonce and wait condition of `options`
+    pub fn run_with_memo_with_options(
+        &self,
+        pass: Option<&str>,
+        memo: &[u8],
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
         async_std::task::block_on(async move {
             let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
                 .set_url(primitives::url())
@@ -28,31 +145,417 @@ impl Transaction {
                 Err(e) => return Err(e),
             };
 
-            let sub = match client.subscribe_events().await {
-                Ok(s) => s,
-                Err(e) => return Err(e),
+            let calls = vec![
+                client.encode(TransferCall {
+                    to: &self.reciever,
+                    amount: self.amount.plancks(),
+                })?,
+                client.encode(RemarkCall::<IndracoreNodeRuntime> {
+                    _runtime: Default::default(),
+                    remark: memo.to_vec(),
+                })?,
+            ];
+            let call = BatchAllCall::<IndracoreNodeRuntime> {
+                _runtime: Default::default(),
+                calls,
             };
+            self.submit_async(pass, call, self.amount.plancks(), options, None)
+                .await
+        })
+    }
 
-            let mut decoder = EventsDecoder::<IndracoreNodeRuntime>::new(client.metadata().clone());
-            decoder.with_balances();
-            let mut sub = EventSubscription::<IndracoreNodeRuntime>::new(sub, decoder);
-            sub.filter_event::<TransferEvent<_>>();
+    /// the most the sender can transfer once the fee is paid, keeping the
+    /// existential deposit if `keep_alive` or if the account cannot be reaped
+    pub fn max_amount(&self, pass: Option<&str>, keep_alive: bool) -> Result<Balance, Error> {
+        self.max_amount_with_options(pass, keep_alive, &TxOptions::default())
+    }
 
-            let hash = match client.transfer(&sender, &self.reciever, self.amount).await {
-                Ok(hash) => hash,
+    /// like `max_amount`, for a transfer with the era, tip and nonce of `options`
+    ///
+    /// The fee is estimated on the transfer signed by the sender as it would be
+    /// submitted, plus a tenth for fee changes before it is included. The tip is
+    /// kept back as well.
+    pub fn max_amount_with_options(
+        &self,
+        pass: Option<&str>,
+        keep_alive: bool,
+        options: &TxOptions,
+    ) -> Result<Balance, Error> {
+        let sender = self.sender.to_signer(pass)?;
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
                 Err(e) => return Err(e),
             };
+            let info = client.account(sender.account_id(), None).await?;
+            let frozen = std::cmp::max(info.data.misc_frozen, info.data.fee_frozen);
+            let usable = info.data.free.saturating_sub(frozen);
+
+            // the amount is at most `usable`, so its encoding is not longer
+            let fee = if keep_alive {
+                let call = TransferKeepAliveCall {
+                    to: &self.reciever,
+                    amount: usable,
+                };
+                fee::estimate_signed(&client, call, &sender, options).await?
+            } else {
+                let call = TransferCall {
+                    to: &self.reciever,
+                    amount: usable,
+                };
+                fee::estimate_signed(&client, call, &sender, options).await?
+            };
 
-            let raw = sub.next().await.unwrap().unwrap();
-            let event = TransferEvent::<IndracoreNodeRuntime>::decode(&mut &raw.data[..]);
-            if let Ok(event) = event {
-                println!("{:?}", event)
+            let pinned = keep_alive || frozen > 0 || info.data.reserved > 0;
+            let existential_deposit = if pinned {
+                check_balance::existential_deposit_of(&client)?
             } else {
+                0
+            };
+            Ok(sweepable(
+                usable,
+                fee.partial_fee,
+                options.tip.plancks(),
+                existential_deposit,
+            )
+            .into())
+        })
+    }
+
+    /// transfer everything returned by `max_amount`, `amount` is ignored
+    pub fn sweep(&self, pass: Option<&str>, keep_alive: bool) -> Result<TransferReceipt, Error> {
+        self.sweep_with_options(pass, keep_alive, &TxOptions::default())
+    }
+
+    /// like `sweep`, with the era, tip, nonce and wait condition of `options`,
+    /// see `max_amount_with_options`
+    pub fn sweep_with_options(
+        &self,
+        pass: Option<&str>,
+        keep_alive: bool,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let amount = self.sweep_amount(pass, keep_alive, options)?;
+        self.transfer_amount(pass, amount, keep_alive, options)
+    }
+
+    /// what `sweep_with_options` transfers
+    pub(crate) fn sweep_amount(
+        &self,
+        pass: Option<&str>,
+        keep_alive: bool,
+        options: &TxOptions,
+    ) -> Result<Balance, Error> {
+        let amount = self
+            .max_amount_with_options(pass, keep_alive, options)?
+            .plancks();
+        if amount == 0 {
+            return Err(Error::Other("Nothing left to transfer".into()));
+        }
+        Ok(amount.into())
+    }
+
+    /// transfer `amount` instead of `self.amount`
+    pub(crate) fn transfer_amount(
+        &self,
+        pass: Option<&str>,
+        amount: Balance,
+        keep_alive: bool,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let amount = amount.plancks();
+        if keep_alive {
+            let call = TransferKeepAliveCall {
+                to: &self.reciever,
+                amount,
+            };
+            self.submit(pass, call, amount, options)
+        } else {
+            let call = TransferCall {
+                to: &self.reciever,
+                amount,
+            };
+            self.submit(pass, call, amount, options)
+        }
+    }
+
+    /// resubmit the transfer in place of the pending extrinsic with `nonce`,
+    /// `options.tip` has to be higher than the tip of the pending extrinsic
+    pub fn replace(
+        &self,
+        pass: Option<&str>,
+        nonce: u32,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let options = TxOptions {
+            nonce: Some(nonce),
+            ..options.clone()
+        };
+        self.run_with_options(pass, &options)
+    }
+
+    /// like `run_with_options`, but take the nonce from `nonces` so many transfers
+    /// of the same sender can be awaited concurrently, `nonces` has to be for the
+    /// sender account
+    pub async fn run_with_nonces(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+        nonces: &NonceManager,
+    ) -> Result<TransferReceipt, Error> {
+        let call = TransferCall {
+            to: &self.reciever,
+            amount: self.amount.plancks(),
+        };
+        self.submit_async(pass, call, self.amount.plancks(), options, Some(nonces))
+            .await
+    }
+
+    fn submit<C>(
+        &self,
+        pass: Option<&str>,
+        call: C,
+        amount: u128,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error>
+    where
+        C: Call<IndracoreNodeRuntime> + Send + Sync,
+    {
+        async_std::task::block_on(self.submit_async(pass, call, amount, options, None))
+    }
+
+    async fn submit_async<C>(
+        &self,
+        pass: Option<&str>,
+        call: C,
+        amount: u128,
+        options: &TxOptions,
+        nonces: Option<&NonceManager>,
+    ) -> Result<TransferReceipt, Error>
+    where
+        C: Call<IndracoreNodeRuntime> + Send + Sync,
+    {
+        let sender = match self.sender.to_signer(pass) {
+            Ok(pair) => pair,
+            Err(e) => return Err(e),
+        };
+
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let mut options = options.clone();
+        if let Some(nonces) = nonces {
+            if nonces.account_id() != sender.account_id() {
                 return Err(Error::Other(
-                    "!!! Failed to subscribe to Balances::Transfer Event".into(),
+                    "Nonce manager belongs to another account than the sender".into(),
                 ));
             }
-            Ok(hash)
-        })
+            options.nonce = Some(nonces.reserve().await?);
+        }
+        let mut decoder = client.events_decoder::<C>();
+        decoder.with_balances();
+        let submitted = async {
+            let extrinsic = extrinsic::create_signed(&client, call, &sender, &options).await?;
+            let encoded = extrinsic.encode();
+            let included =
+                extrinsic::submit_and_watch(&client, extrinsic, decoder, &options).await?;
+            Ok::<_, Error>((encoded, included))
+        };
+        let (encoded, included) = match submitted.await {
+            Ok(submitted) => submitted,
+            Err(e) => {
+                // the reserved nonce may be unused or used now, let the node tell
+                if let Some(nonces) = nonces {
+                    nonces.invalidate().await;
+                }
+                return Err(e);
+            }
+        };
+
+        receipt(
+            &client,
+            &encoded,
+            Some(sender.account_id()),
+            amount,
+            options.tip.plancks(),
+            included,
+        )
+        .await
+    }
+}
+
+/// Cancel the pending extrinsic of `sender` with `nonce` by replacing it with a
+/// `system.remark`. `tip` has to be higher than the tip of the pending extrinsic.
+pub fn cancel<S: ToSigner>(
+    sender: &S,
+    pass: Option<&str>,
+    nonce: u32,
+    tip: impl Into<Balance>,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+    let signer = sender.to_signer(pass)?;
+
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let options = TxOptions {
+            nonce: Some(nonce),
+            tip: tip.into(),
+            ..Default::default()
+        };
+        let call = RemarkCall {
+            _runtime: Default::default(),
+            remark: Vec::new(),
+        };
+        extrinsic::watch(&client, call, &signer, &options).await
+    })
+}
+
+/// Move `amount` from `source` to `dest` with `balances.force_transfer`, wrapped in
+/// `sudo.sudo`. Only meant for test networks where `sudo` holds the sudo key.
+pub fn force_transfer<S: ToSigner>(
+    sudo: &S,
+    pass: Option<&str>,
+    source: &primitives::IndracoreId,
+    dest: &primitives::IndracoreId,
+    amount: impl Into<Balance>,
+) -> Result<TransferReceipt, Error> {
+    let signer = sudo.to_signer(pass)?;
+    let amount = amount.into().plancks();
+
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let call = client.encode(ForceTransferCall {
+            source,
+            dest,
+            value: amount,
+        })?;
+        let sudo_call = SudoCall {
+            _runtime: Default::default(),
+            call: &call,
+        };
+        let mut decoder = client.events_decoder::<SudoCall<IndracoreNodeRuntime>>();
+        decoder.with_balances();
+        let options = TxOptions::default();
+        let extrinsic = extrinsic::create_signed(&client, sudo_call, &signer, &options).await?;
+        let encoded = extrinsic.encode();
+        let included = extrinsic::submit_and_watch(&client, extrinsic, decoder, &options).await?;
+
+        // sudo succeeds even if the call it dispatched failed
+        let sudid = included
+            .events
+            .iter()
+            .find(|event| event.module == "Sudo" && event.variant == "Sudid")
+            .ok_or_else(|| Error::Other("Failed to find a Sudo::Sudid event".into()))?;
+        match DispatchResult::decode(&mut &sudid.data[..]) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(Error::Other(format!("Sudo call failed: {:?}", e))),
+            Err(e) => return Err(e.into()),
+        }
+
+        receipt(
+            &client,
+            &encoded,
+            None,
+            amount,
+            options.tip.plancks(),
+            included,
+        )
+        .await
+    })
+}
+
+/// What is left of `usable` once the fee with its margin, the tip and the
+/// `existential_deposit` are kept back.
+fn sweepable(usable: u128, fee: u128, tip: u128, existential_deposit: u128) -> u128 {
+    usable
+        .saturating_sub(fee::with_margin(fee))
+        .saturating_sub(tip)
+        .saturating_sub(existential_deposit)
+}
+
+async fn receipt(
+    client: &primitives::Client,
+    encoded: &[u8],
+    sender: Option<&AccountId32>,
+    amount: u128,
+    tip: u128,
+    included: Included,
+) -> Result<TransferReceipt, Error> {
+    let header = match client.header(Some(included.block_hash)).await? {
+        Some(header) => header,
+        None => {
+            return Err(Error::Other(format!(
+                "Block {:?} not found",
+                included.block_hash
+            )))
+        }
+    };
+
+    // fees are charged against the state the extrinsic was applied on
+    let rpc = rpc::client().await?;
+    let info = rpc::payment_query_info(&rpc, encoded, Some(*header.parent_hash())).await?;
+
+    let mut transfer = None;
+    let mut events = Vec::new();
+    for event in included.events {
+        if transfer.is_none() && event.module == "Balances" && event.variant == "Transfer" {
+            let decoded = TransferEvent::<IndracoreNodeRuntime>::decode(&mut &event.data[..])?;
+            let from_sender = sender.map_or(true, |sender| &decoded.from == sender);
+            if from_sender && decoded.amount == amount {
+                transfer = Some(decoded);
+                continue;
+            }
+        }
+        events.push(event);
+    }
+    let transfer = transfer
+        .ok_or_else(|| Error::Other("Failed to find the Balances::Transfer event".into()))?;
+
+    Ok(TransferReceipt {
+        block_hash: included.block_hash,
+        block_number: *header.number(),
+        extrinsic_hash: included.extrinsic_hash,
+        extrinsic_index: included.extrinsic_index,
+        transfer,
+        estimated_fee: info.partial_fee.saturating_add(tip),
+        events,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::transaction::sweepable;
+
+    #[test]
+    fn test_sweepable() {
+        assert_eq!(sweepable(10_000, 1_000, 0, 0), 8_900);
+        assert_eq!(sweepable(10_000, 1_000, 50, 500), 8_350);
+        assert_eq!(sweepable(1_000, 1_000, 0, 0), 0);
+        assert_eq!(sweepable(u128::max_value(), u128::max_value(), 0, 0), 0);
     }
 }
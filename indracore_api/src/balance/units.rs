@@ -0,0 +1,149 @@
+use crate::{primitives, util};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use substrate_subxt::Error;
+
+/// An amount of the chain token, held in plancks, the smallest unit.
+///
+/// Parses token amounts like `1.5 SEL`, `250 mSEL` or `1_000` (token units)
+/// and `100 planck`, using the chain decimals set by `DECIMAL`.
+///
+/// Serializes as the bare number of plancks.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+pub struct Balance(u128);
+
+impl Balance {
+    pub const fn from_plancks(plancks: u128) -> Self {
+        Balance(plancks)
+    }
+
+    pub const fn plancks(&self) -> u128 {
+        self.0
+    }
+
+    /// parse `input` for a token with `decimals` decimals named `symbol`
+    pub fn parse(input: &str, decimals: u8, symbol: &str) -> Result<Self, Error> {
+        let input = input.trim().replace('_', "");
+        let split = input
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or_else(|| input.len());
+        let (number, unit) = input.split_at(split);
+        let unit = unit.trim();
+
+        let exponent = if unit.is_empty() {
+            decimals as i32
+        } else if unit == "planck" || unit == "plancks" {
+            0
+        } else if unit.ends_with(symbol) {
+            let shift = match &unit[..unit.len() - symbol.len()] {
+                "" => 0,
+                "k" => 3,
+                "M" => 6,
+                "m" => -3,
+                "u" | "µ" => -6,
+                "n" => -9,
+                "p" => -12,
+                prefix => return Err(Error::Other(format!("Unknown unit prefix '{}'", prefix))),
+            };
+            decimals as i32 + shift
+        } else {
+            return Err(Error::Other(format!("Unknown unit '{}'", unit)));
+        };
+        if exponent < 0 {
+            return Err(Error::Other(format!("Unit '{}' is below one planck", unit)));
+        }
+
+        let (whole, fraction) = match number.find('.') {
+            Some(dot) => (&number[..dot], &number[dot + 1..]),
+            None => (number, ""),
+        };
+        if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+            return Err(Error::Other(format!("Invalid amount '{}'", input)));
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > exponent as usize {
+            return Err(Error::Other(format!(
+                "Amount '{}' is more precise than one planck",
+                input
+            )));
+        }
+
+        let digits = format!("{}{:0<width$}", whole, fraction, width = exponent as usize);
+        match digits.trim_start_matches('0') {
+            "" => Ok(Balance(0)),
+            digits => match digits.parse() {
+                Ok(plancks) => Ok(Balance(plancks)),
+                Err(_) => Err(Error::Other(format!("Amount '{}' is too large", input))),
+            },
+        }
+    }
+
+    /// format in token units for a token with `decimals` decimals named `symbol`
+    pub fn format(&self, decimals: u8, symbol: &str) -> String {
+        format!("{} {}", util::format_units(self.0, decimals), symbol)
+    }
+}
+
+impl From<u128> for Balance {
+    fn from(plancks: u128) -> Self {
+        Balance(plancks)
+    }
+}
+
+impl From<Balance> for u128 {
+    fn from(balance: Balance) -> Self {
+        balance.0
+    }
+}
+
+impl FromStr for Balance {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Balance::parse(s, primitives::decimals(), &primitives::symbol())
+    }
+}
+
+impl fmt::Display for Balance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(primitives::decimals(), &primitives::symbol()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::units::Balance;
+
+    #[test]
+    fn test_parse() {
+        let parse = |s| Balance::parse(s, 12, "SEL").map(|b| b.plancks());
+        assert_eq!(parse("1.5 SEL").unwrap(), 1_500_000_000_000);
+        assert_eq!(parse("1.5SEL").unwrap(), 1_500_000_000_000);
+        assert_eq!(parse("250 mSEL").unwrap(), 250_000_000_000);
+        assert_eq!(parse("1_000").unwrap(), 1_000_000_000_000_000);
+        assert_eq!(parse(".5 kSEL").unwrap(), 500_000_000_000_000);
+        assert_eq!(parse("100 planck").unwrap(), 100);
+        assert_eq!(parse("0 SEL").unwrap(), 0);
+        assert_eq!(parse("1 pSEL").unwrap(), 1);
+
+        assert!(parse("1.5 DOT").is_err());
+        assert!(parse("1.5 xSEL").is_err());
+        assert!(parse("0.5 pSEL").is_err());
+        assert!(parse("1.2.3 SEL").is_err());
+        assert!(parse("SEL").is_err());
+        assert_eq!(parse("1 nSEL").unwrap(), 1_000);
+        assert!(parse("1000000000000000000000000000000 SEL").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            Balance::from(1_500_000_000_000).format(12, "SEL"),
+            "1.5 SEL"
+        );
+        assert_eq!(Balance::from(1).format(12, "SEL"), "0.000000000001 SEL");
+        assert_eq!(Balance::from(0).format(12, "SEL"), "0 SEL");
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    balance::{check_balance, transaction::Transaction, units::Balance},
+    extrinsic::TxOptions,
+    fee, indices, keyring, primitives,
+    signer::ToSigner,
+};
+use pallet_indices::address::Address;
+use std::{cmp, fmt};
+use substrate_subxt::{
+    balances::{AccountData, TransferCall},
+    sp_core::crypto::{AccountId32, Ss58Codec},
+    system::AccountStoreExt,
+    ClientBuilder, Error, IndracoreNodeRuntime, Signer,
+};
+
+/// A reason a transfer would fail on chain or lose funds.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Problem {
+    /// the address is neither SS58 nor an account index, or not the recipient
+    InvalidAddress(String),
+    /// the address is encoded for another network
+    WrongNetwork { expected: u8, found: u8 },
+    /// the recipient index is not assigned to any account
+    UnknownIndex(u32),
+    /// the sender cannot pay the amount and the fee from what is not frozen
+    InsufficientBalance {
+        spendable: Balance,
+        required: Balance,
+    },
+    /// the sender is left with less than the existential deposit, which is
+    /// lost when the account is reaped
+    SenderReaped { remaining: Balance },
+    /// the amount is too small to create the recipient account
+    BelowExistentialDeposit {
+        amount: Balance,
+        existential_deposit: Balance,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::InvalidAddress(e) => write!(f, "Invalid address: {}", e),
+            Problem::WrongNetwork { expected, found } => write!(
+                f,
+                "Address is for network {}, expected network {}",
+                found, expected
+            ),
+            Problem::UnknownIndex(index) => write!(f, "Account index {} is not assigned", index),
+            Problem::InsufficientBalance {
+                spendable,
+                required,
+            } => write!(
+                f,
+                "Spendable balance {} is lower than the required {}",
+                spendable, required
+            ),
+            Problem::SenderReaped { remaining } => write!(
+                f,
+                "Sender account is reaped and the remaining {} is lost",
+                remaining
+            ),
+            Problem::BelowExistentialDeposit {
+                amount,
+                existential_deposit,
+            } => write!(
+                f,
+                "Amount {} is below the existential deposit {} of the new recipient account",
+                amount, existential_deposit
+            ),
+        }
+    }
+}
+
+/// check that `address` is an account index or an SS58 address or index of this
+/// network, as set by `SS58`
+pub fn check_address(address: &str) -> Vec<Problem> {
+    address_problems(address, primitives::ss58_format())
+}
+
+impl<S: ToSigner> Transaction<S> {
+    /// check the transfer against the current chain state without submitting it,
+    /// nothing is signed and no fee is spent, an empty list means no problem was found
+    ///
+    /// `address` is the address `reciever` was parsed from, it is checked with
+    /// `check_address` and has to resolve to `reciever`
+    pub fn validate(&self, pass: Option<&str>, address: &str) -> Result<Vec<Problem>, Error> {
+        self.validate_with_options(pass, address, &TxOptions::default())
+    }
+
+    /// like `validate`, for a transfer with the tip of `options`
+    pub fn validate_with_options(
+        &self,
+        pass: Option<&str>,
+        address: &str,
+        options: &TxOptions,
+    ) -> Result<Vec<Problem>, Error> {
+        let problems = check_address(address);
+        if !problems.is_empty() {
+            return Ok(problems);
+        }
+        match keyring::indracoreid(address) {
+            Ok(id) if id == self.reciever => {}
+            _ => {
+                return Ok(vec![Problem::InvalidAddress(format!(
+                    "{} is not the recipient of the transfer",
+                    address.trim()
+                ))])
+            }
+        }
+
+        let sender = self.sender.to_signer(pass)?;
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
+                Err(e) => return Err(e),
+            };
+
+            let reciever: AccountId32 = match &self.reciever {
+                Address::Id(id) => id.clone(),
+                Address::Index(index) => match indices::lookup(*index).await? {
+                    Some(id) => id,
+                    None => return Ok(vec![Problem::UnknownIndex(*index)]),
+                },
+            };
+            let sender_info = client.account(sender.account_id(), None).await?;
+            let reciever_info = client.account(&reciever, None).await?;
+            let fee = fee::estimate(TransferCall {
+                to: &self.reciever,
+                amount: self.amount.plancks(),
+            })
+            .await?;
+            let existential_deposit = check_balance::existential_deposit_of(&client)?;
+
+            Ok(balance_problems(
+                &sender_info.data,
+                &reciever_info.data,
+                self.amount.plancks(),
+                fee.partial_fee.saturating_add(options.tip.plancks()),
+                existential_deposit,
+            ))
+        })
+    }
+}
+
+fn address_problems(address: &str, expected: u8) -> Vec<Problem> {
+    let address = address.trim();
+    // plain indices carry no network, `validate` checks they are assigned
+    if !address.is_empty() && address.bytes().all(|b| b.is_ascii_digit()) {
+        return match address.parse::<u32>() {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![Problem::InvalidAddress(format!("{:?}", e))],
+        };
+    }
+    let found = match keyring::ss58_index(address) {
+        Some((_, found)) => found,
+        None => match AccountId32::from_ss58check_with_version(address) {
+            Ok((_, format)) => u8::from(format),
+            Err(e) => return vec![Problem::InvalidAddress(format!("{:?}", e))],
+        },
+    };
+    if found == expected {
+        Vec::new()
+    } else {
+        vec![Problem::WrongNetwork { expected, found }]
+    }
+}
+
+fn balance_problems(
+    sender: &AccountData<u128>,
+    reciever: &AccountData<u128>,
+    amount: u128,
+    fee: u128,
+    existential_deposit: u128,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let spendable = sender
+        .free
+        .saturating_sub(cmp::max(sender.misc_frozen, sender.fee_frozen));
+    let required = amount.saturating_add(fee);
+    if required > spendable {
+        problems.push(Problem::InsufficientBalance {
+            spendable: spendable.into(),
+            required: required.into(),
+        });
+    } else {
+        let remaining = sender.free - required;
+        let total = remaining.saturating_add(sender.reserved);
+        if remaining > 0 && total < existential_deposit {
+            problems.push(Problem::SenderReaped {
+                remaining: remaining.into(),
+            });
+        }
+    }
+
+    let exists = reciever.free.saturating_add(reciever.reserved) > 0;
+    if !exists && amount < existential_deposit {
+        problems.push(Problem::BelowExistentialDeposit {
+            amount: amount.into(),
+            existential_deposit: existential_deposit.into(),
+        });
+    }
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use crate::balance::validate::{address_problems, balance_problems, Problem};
+    use substrate_subxt::balances::AccountData;
+
+    fn data(free: u128, frozen: u128) -> AccountData<u128> {
+        AccountData {
+            free,
+            reserved: 0,
+            misc_frozen: frozen,
+            fee_frozen: frozen,
+        }
+    }
+
+    #[test]
+    fn test_address_problems() {
+        let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+        assert!(address_problems(alice, 42).is_empty());
+        assert!(address_problems("42", 0).is_empty());
+        assert_eq!(
+            address_problems(alice, 0),
+            vec![Problem::WrongNetwork {
+                expected: 0,
+                found: 42
+            }]
+        );
+        assert!(matches!(
+            address_problems("5GrwvaEF", 42)[..],
+            [Problem::InvalidAddress(_)]
+        ));
+        assert!(matches!(
+            address_problems("+42", 42)[..],
+            [Problem::InvalidAddress(_)]
+        ));
+        // index 42 SS58 encoded for network 42
+        let mut data = vec![42u8, 42];
+        let mut hasher = blake2_rfc::blake2b::Blake2b::new(64);
+        hasher.update(b"SS58PRE");
+        hasher.update(&data);
+        data.push(hasher.finalize().as_bytes()[0]);
+        let index = bs58::encode(data).into_string();
+        assert!(address_problems(&index, 42).is_empty());
+        assert_eq!(
+            address_problems(&index, 0),
+            vec![Problem::WrongNetwork {
+                expected: 0,
+                found: 42
+            }]
+        );
+    }
+
+    #[test]
+    fn test_balance_problems() {
+        assert!(balance_problems(&data(1_000, 0), &data(500, 0), 800, 10, 100).is_empty());
+        // sweeping the whole account reaps it without losing anything
+        assert!(balance_problems(&data(1_000, 0), &data(500, 0), 990, 10, 100).is_empty());
+        assert_eq!(
+            balance_problems(&data(1_000, 300), &data(500, 0), 800, 10, 100),
+            vec![Problem::InsufficientBalance {
+                spendable: 700.into(),
+                required: 810.into()
+            }]
+        );
+        assert_eq!(
+            balance_problems(&data(1_000, 0), &data(500, 0), 950, 10, 100),
+            vec![Problem::SenderReaped {
+                remaining: 40.into()
+            }]
+        );
+        assert_eq!(
+            balance_problems(&data(1_000, 0), &data(0, 0), 50, 10, 100),
+            vec![Problem::BelowExistentialDeposit {
+                amount: 50.into(),
+                existential_deposit: 100.into()
+            }]
+        );
+    }
+}
@@ -1,22 +1,34 @@
 use crate::contract::transcode::Transcoder;
-use crate::primitives;
+use crate::{
+    balance::units::Balance,
+    extrinsic::{self, TxOptions},
+    fee::{self, FeeEstimate},
+    keyring::Sr25519,
+    offline::{self, OfflineParams},
+    primitives,
+    signer::ToSigner,
+};
 use substrate_subxt::{
-    balances::Balances, contracts::*, system::System, ClientBuilder, Error, ExtrinsicSuccess,
-    IndracoreNodeRuntime,
+    contracts::*, system::System, ClientBuilder, Error, ExtrinsicSuccess, IndracoreNodeRuntime,
 };
 
-pub struct ContarctCall {
+pub struct ContarctCall<S: ToSigner = Sr25519> {
     pub name: String,
     pub args: Vec<String>,
     pub metadata: String,
-    pub signer: primitives::Sr25519,
-    pub value: <IndracoreNodeRuntime as Balances>::Balance,
+    pub signer: S,
+    pub value: Balance,
     pub gas_limit: u64,
     pub contract: <IndracoreNodeRuntime as System>::Address,
 }
 
-impl ContarctCall {
-    async fn call(&self, data: Vec<u8>) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+impl<S: ToSigner> ContarctCall<S> {
+    async fn call(
+        &self,
+        signer: &S::Signer,
+        data: Vec<u8>,
+        options: &TxOptions,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
         let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
             .set_url(primitives::url())
             .build()
@@ -25,30 +37,71 @@ impl ContarctCall {
             Ok(cli) => cli,
             Err(e) => return Err(e),
         };
-        let extrinsic_success = client
-            .call_and_watch(
-                &self.signer,
-                &self.contract,
-                self.value,
-                self.gas_limit,
-                &data,
-            )
-            .await?;
+        let call = CallCall {
+            dest: &self.contract,
+            value: self.value.plancks(),
+            gas_limit: self.gas_limit,
+            data: &data,
+        };
+        let extrinsic_success = extrinsic::watch(&client, call, signer, options).await?;
         Ok(extrinsic_success)
     }
 
-    pub fn run(&self) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+    /// encode the message call data from the contract metadata
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
         let metadata = match super::load_metadata(&self.metadata) {
             Ok(m) => m,
             Err(e) => return Err(Error::Other(format!("{:?}", e))),
         };
 
         let transcoder = Transcoder::new(metadata);
-        let data = match transcoder.encode(&self.name, &self.args) {
-            Ok(m) => m,
-            Err(e) => return Err(Error::Other(format!("{:?}", e))),
+        match transcoder.encode(&self.name, &self.args) {
+            Ok(m) => Ok(m),
+            Err(e) => Err(Error::Other(format!("{:?}", e))),
+        }
+    }
+
+    /// estimate the fee of the call without signing it
+    pub fn estimate_fee(&self) -> Result<FeeEstimate, Error> {
+        let data = self.encode()?;
+        async_std::task::block_on(fee::estimate(CallCall {
+            dest: &self.contract,
+            value: self.value.plancks(),
+            gas_limit: self.gas_limit,
+            data: &data,
+        }))
+    }
+
+    /// sign the call without a connection to the node, see `offline::sign`
+    pub fn sign_offline(
+        &self,
+        pass: Option<&str>,
+        params: &OfflineParams,
+    ) -> Result<String, Error> {
+        let signer = self.signer.to_signer(pass)?;
+        let data = self.encode()?;
+        let call = CallCall {
+            dest: &self.contract,
+            value: self.value.plancks(),
+            gas_limit: self.gas_limit,
+            data: &data,
         };
-        let result = async_std::task::block_on(self.call(data))?;
+        offline::sign(call, &signer, params)
+    }
+
+    pub fn run(&self, pass: Option<&str>) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        self.run_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `run`, with the era, tip, nonce and wait condition of `options`
+    pub fn run_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let signer = self.signer.to_signer(pass)?;
+        let data = self.encode()?;
+        let result = async_std::task::block_on(self.call(&signer, data, options))?;
 
         Ok(result)
     }
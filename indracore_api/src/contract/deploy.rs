@@ -1,14 +1,20 @@
-use crate::primitives;
+use crate::{
+    extrinsic::{self, TxOptions},
+    fee::{self, FeeEstimate},
+    keyring::Sr25519,
+    primitives,
+    signer::ToSigner,
+};
 use std::{fs, io::Read, path::PathBuf};
 
-pub struct ContractDeploy {
+pub struct ContractDeploy<S: ToSigner = Sr25519> {
     pub wasm_path: PathBuf,
-    pub signer: primitives::Sr25519,
+    pub signer: S,
 }
 use substrate_subxt::{contracts::*, ClientBuilder, Error, IndracoreNodeRuntime};
 
-impl ContractDeploy {
-    fn load_contract(&self) -> Result<Vec<u8>, Error> {
+impl<S: ToSigner> ContractDeploy<S> {
+    pub(crate) fn load_contract(&self) -> Result<Vec<u8>, Error> {
         let contract_wasm_path = self.wasm_path.clone();
         let mut data: Vec<u8> = Vec::new();
 
@@ -23,8 +29,27 @@ impl ContractDeploy {
         }
     }
 
+    /// estimate the fee of putting the contract code without signing it
+    pub fn estimate_fee(&self) -> Result<FeeEstimate, Error> {
+        let code = self.load_contract()?;
+        async_std::task::block_on(fee::estimate(PutCodeCall {
+            _runtime: Default::default(),
+            code: &code,
+        }))
+    }
+
     ///put contract code to indracoe chain
-    pub fn exec(&self) -> Result<sp_core::H256, Error> {
+    pub fn exec(&self, pass: Option<&str>) -> Result<sp_core::H256, Error> {
+        self.exec_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `exec`, with the era, tip, nonce and wait condition of `options`
+    pub fn exec_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<sp_core::H256, Error> {
+        let signer = self.signer.to_signer(pass)?;
         let code = match self.load_contract() {
             Ok(code) => code,
             Err(e) => return Err(e),
@@ -39,7 +64,11 @@ impl ContractDeploy {
                 Ok(cli) => cli,
                 Err(e) => return Err(e),
             };
-            let result = client.put_code_and_watch(&self.signer, &code).await?;
+            let call = PutCodeCall {
+                _runtime: Default::default(),
+                code: &code,
+            };
+            let result = extrinsic::watch(&client, call, &signer, options).await?;
             let code_stored = result
                 .code_stored()?
                 .ok_or_else(|| Error::Other("Failed to find a CodeStored event".into()))?;
@@ -51,21 +80,18 @@ impl ContractDeploy {
 #[cfg(test)]
 mod test {
     use crate::contract::deploy::ContractDeploy;
-    use crate::keyring::parse_code_hash;
+    use crate::keyring::{parse_code_hash, Sr25519};
     use sp_keyring::AccountKeyring;
     use std::path::PathBuf;
-    use substrate_subxt::{sp_core::sr25519::Pair, IndracoreNodeRuntime, PairSigner};
 
     #[test]
     fn test_deploy() {
         let mut wasm_path = PathBuf::new();
         wasm_path.push("/data/project/indracore-api/indracore_api/src/contract/test/erc20.wasm");
-        let pair = AccountKeyring::Alice.pair();
-
-        let signer = PairSigner::<IndracoreNodeRuntime, Pair>::new(pair);
+        let signer = Sr25519::from_pair(AccountKeyring::Alice.pair());
 
         let deploy = ContractDeploy { wasm_path, signer };
-        let result = deploy.exec().unwrap();
+        let result = deploy.exec(None).unwrap();
 
         let code_hash =
             parse_code_hash("0x40f8c7c624d1d8fbd0873a381c63a0858b4d75315bd8ca62e0111068bbf138e3");
@@ -1,29 +1,78 @@
 use crate::contract::transcode::Transcoder;
-use crate::primitives;
-use substrate_subxt::{contracts::*, system::System, ClientBuilder, Error, IndracoreNodeRuntime};
+use crate::{
+    balance::units::Balance,
+    extrinsic::{self, TxOptions},
+    fee::{self, FeeEstimate},
+    keyring::Sr25519,
+    primitives,
+    signer::ToSigner,
+};
+use substrate_subxt::{
+    contracts::*,
+    sp_core::{crypto::AccountId32, H256},
+    sp_runtime::traits::{BlakeTwo256, Hash},
+    system::System,
+    ClientBuilder, Error, IndracoreNodeRuntime,
+};
 
-pub struct Instantiate {
+pub struct Instantiate<S: ToSigner = Sr25519> {
     pub name: String,
     pub args: Vec<String>,
     pub metadata: String,
-    pub signer: primitives::Sr25519,
-    pub endowment: u128,
+    pub signer: S,
+    pub endowment: Balance,
     pub gas_limit: u64,
     pub code_hash: <IndracoreNodeRuntime as System>::Hash,
 }
 
-impl Instantiate {
-    pub fn instantiate(&self) -> Result<InstantiatedEvent<IndracoreNodeRuntime>, Error> {
+impl<S: ToSigner> Instantiate<S> {
+    /// encode the constructor call data from the contract metadata
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
         let metadata = match super::load_metadata(&self.metadata) {
             Ok(m) => m,
             Err(e) => return Err(Error::Other(format!("{:?}", e))),
         };
 
         let transcoder = Transcoder::new(metadata);
-        let data = match transcoder.encode(&self.name, &self.args) {
-            Ok(m) => m,
-            Err(e) => return Err(Error::Other(format!("{:?}", e))),
-        };
+        match transcoder.encode(&self.name, &self.args) {
+            Ok(m) => Ok(m),
+            Err(e) => Err(Error::Other(format!("{:?}", e))),
+        }
+    }
+
+    /// the address of the contract `deployer` instantiates, it is only known
+    /// ahead if the same constructor data was not instantiated before
+    pub fn address(&self, deployer: &AccountId32) -> Result<AccountId32, Error> {
+        let data = self.encode()?;
+        Ok(contract_address(&self.code_hash, &data, deployer))
+    }
+
+    /// estimate the fee of the instantiation without signing it
+    pub fn estimate_fee(&self) -> Result<FeeEstimate, Error> {
+        let data = self.encode()?;
+        async_std::task::block_on(fee::estimate(InstantiateCall {
+            endowment: self.endowment.plancks(),
+            gas_limit: self.gas_limit,
+            code_hash: &self.code_hash,
+            data: &data,
+        }))
+    }
+
+    pub fn instantiate(
+        &self,
+        pass: Option<&str>,
+    ) -> Result<InstantiatedEvent<IndracoreNodeRuntime>, Error> {
+        self.instantiate_with_options(pass, &TxOptions::default())
+    }
+
+    /// like `instantiate`, with the era, tip, nonce and wait condition of `options`
+    pub fn instantiate_with_options(
+        &self,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<InstantiatedEvent<IndracoreNodeRuntime>, Error> {
+        let signer = self.signer.to_signer(pass)?;
+        let data = self.encode()?;
         async_std::task::block_on(async move {
             let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
                 .set_url(primitives::url())
@@ -33,15 +82,13 @@ impl Instantiate {
                 Ok(cli) => cli,
                 Err(e) => return Err(e),
             };
-            let result = client
-                .instantiate_and_watch(
-                    &self.signer,
-                    self.endowment,
-                    self.gas_limit,
-                    &self.code_hash,
-                    &data,
-                )
-                .await?;
+            let call = InstantiateCall {
+                endowment: self.endowment.plancks(),
+                gas_limit: self.gas_limit,
+                code_hash: &self.code_hash,
+                data: &data,
+            };
+            let result = extrinsic::watch(&client, call, &signer, options).await?;
 
             let instantiated = result
                 .instantiated()?
@@ -52,10 +99,39 @@ impl Instantiate {
     }
 }
 
+/// The address of a contract as the `SimpleAddressDeterminer` of the runtime
+/// derives it, from the code hash, the hash of the constructor data and the
+/// deployer.
+pub(crate) fn contract_address(
+    code_hash: &H256,
+    data: &[u8],
+    deployer: &AccountId32,
+) -> AccountId32 {
+    let mut buf = Vec::with_capacity(96);
+    buf.extend_from_slice(code_hash.as_ref());
+    buf.extend_from_slice(BlakeTwo256::hash(data).as_ref());
+    buf.extend_from_slice(deployer.as_ref());
+    AccountId32::from(BlakeTwo256::hash(&buf).to_fixed_bytes())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::contract::instantiate::Instantiate;
+    use crate::contract::instantiate::{contract_address, Instantiate};
     use crate::keyring;
+    use substrate_subxt::sp_core::{crypto::AccountId32, H256};
+
+    #[test]
+    fn test_contract_address() {
+        let address = contract_address(
+            &H256::repeat_byte(1),
+            &[0xd1, 0x83, 0x51, 0x2b],
+            &AccountId32::from([2; 32]),
+        );
+        assert_eq!(
+            hex::encode(AsRef::<[u8]>::as_ref(&address)),
+            "7bd570538c2c3d21eeeece1558e1b30677f30b46b517b44b91dc77aa1894bbf1"
+        );
+    }
 
     #[test]
     fn test_instantiated() {
@@ -70,11 +146,7 @@ mod test {
         let mut args: Vec<String> = Vec::new();
         args.push("1_000_000_000".to_string());
 
-        let account = keyring::Sr25519 { suri: mnemonic };
-        let signer = match account.pair(None) {
-            Ok(p) => p,
-            Err(_) => panic!(),
-        };
+        let signer = keyring::Sr25519::new(mnemonic);
 
         let inst = Instantiate {
             name: "new".to_string(),
@@ -82,10 +154,10 @@ mod test {
             metadata,
             signer,
             code_hash: code_hash.unwrap(),
-            endowment: 1_000_000_000_000_000,
+            endowment: 1_000_000_000_000_000.into(),
             gas_limit: 500_000_000_000,
         };
 
-        assert!(inst.instantiate().is_ok())
+        assert!(inst.instantiate(None).is_ok())
     }
 }
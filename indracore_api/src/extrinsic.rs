@@ -0,0 +1,493 @@
+use crate::{balance::units::Balance, primitives, rpc};
+use async_std::future::timeout;
+use futures::channel::mpsc::UnboundedSender;
+use jsonrpsee::{client::Subscription, common::Params};
+use serde::Deserialize;
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+use substrate_subxt::{
+    sp_core::{crypto::AccountId32, Bytes, Encode, H256},
+    sp_runtime::{
+        generic::Era,
+        traits::{BlakeTwo256, Hash, Header},
+    },
+    system::Phase,
+    Call, ChargeTransactionPayment, CheckEra, CheckGenesis, CheckNonce, CheckSpecVersion,
+    CheckTxVersion, CheckWeight, Encoded, Error, EventsDecoder, ExtrinsicSuccess,
+    IndracoreNodeRuntime, Raw, RawEvent, SignedPayload, Signer, UncheckedExtrinsic,
+};
+
+/// How long to wait for a submitted extrinsic to be included in a block.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The point at which a submitted extrinsic counts as done.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WaitFor {
+    InBlock,
+    Finalized,
+}
+
+/// How an extrinsic is signed and how long to wait for it.
+#[derive(Clone, Debug)]
+pub struct TxOptions {
+    /// number of blocks the extrinsic stays valid for, immortal if `None`
+    pub mortality: Option<u64>,
+    pub tip: Balance,
+    /// taken from the signer, or from the node, if `None`
+    pub nonce: Option<u32>,
+    pub wait_for: WaitFor,
+    pub timeout: Duration,
+    /// receives every pool status of the extrinsic while it is watched
+    pub on_status: Option<UnboundedSender<TransactionStatus>>,
+}
+
+impl Default for TxOptions {
+    fn default() -> Self {
+        TxOptions {
+            mortality: None,
+            tip: Balance::default(),
+            nonce: None,
+            wait_for: WaitFor::InBlock,
+            timeout: DEFAULT_TIMEOUT,
+            on_status: None,
+        }
+    }
+}
+
+/// The signed extensions of an extrinsic, as passed to `SignedPayload::new`.
+pub(crate) type Extra = (
+    CheckSpecVersion<IndracoreNodeRuntime>,
+    CheckTxVersion<IndracoreNodeRuntime>,
+    CheckGenesis<IndracoreNodeRuntime>,
+    CheckEra<IndracoreNodeRuntime>,
+    CheckNonce<IndracoreNodeRuntime>,
+    CheckWeight<IndracoreNodeRuntime>,
+    ChargeTransactionPayment<IndracoreNodeRuntime>,
+);
+
+/// Everything an extrinsic commits to besides its call and signer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct SigningParams {
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    pub genesis_hash: H256,
+    pub era: Era,
+    /// the block the era starts at, the genesis block if immortal
+    pub block_hash: H256,
+    pub nonce: u32,
+    pub tip: u128,
+}
+
+impl SigningParams {
+    pub fn extra(&self) -> Extra {
+        (
+            CheckSpecVersion(PhantomData, self.spec_version),
+            CheckTxVersion(PhantomData, self.transaction_version),
+            CheckGenesis(PhantomData, self.genesis_hash),
+            CheckEra((self.era, PhantomData), self.block_hash),
+            CheckNonce(self.nonce),
+            CheckWeight(PhantomData),
+            ChargeTransactionPayment(self.tip),
+        )
+    }
+}
+
+/// Resolve the signing parameters of an extrinsic of `account_id` against the node.
+/// `nonce` overrides the next index of the account.
+pub(crate) async fn signing_params(
+    client: &primitives::Client,
+    account_id: &AccountId32,
+    nonce: Option<u32>,
+    options: &TxOptions,
+) -> Result<SigningParams, Error> {
+    let rpc = rpc::client().await?;
+    let version = rpc::runtime_version(&rpc).await?;
+    let genesis = *client.genesis();
+
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => {
+            rpc::request(
+                &rpc,
+                "system_accountNextIndex",
+                vec![rpc::json(account_id)?],
+            )
+            .await?
+        }
+    };
+
+    let (era, block_hash) = match options.mortality {
+        Some(period) => {
+            let head = client.finalized_head().await?;
+            let number = match client.header(Some(head)).await? {
+                Some(header) => *header.number(),
+                None => return Err(Error::Other(format!("Block {:?} not found", head))),
+            };
+            let era = Era::mortal(period, number as u64);
+            let birth = era.birth(number as u64) as u32;
+            match client.block_hash(Some(birth.into())).await? {
+                Some(hash) => (era, hash),
+                None => return Err(Error::Other(format!("Block {} not found", birth))),
+            }
+        }
+        None => (Era::Immortal, genesis),
+    };
+
+    Ok(SigningParams {
+        spec_version: version.spec_version,
+        transaction_version: version.transaction_version,
+        genesis_hash: genesis,
+        era,
+        block_hash,
+        nonce,
+        tip: options.tip.plancks(),
+    })
+}
+
+/// Sign `call` with the era, tip and nonce of `options`.
+pub async fn create_signed<C, S>(
+    client: &primitives::Client,
+    call: C,
+    signer: &S,
+    options: &TxOptions,
+) -> Result<UncheckedExtrinsic<IndracoreNodeRuntime>, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let nonce = options.nonce.or_else(|| signer.nonce());
+    let params = signing_params(client, signer.account_id(), nonce, options).await?;
+    sign(client, call, signer, &params).await
+}
+
+/// Sign `call` with already resolved signing parameters.
+pub(crate) async fn sign<C, S>(
+    client: &primitives::Client,
+    call: C,
+    signer: &S,
+    params: &SigningParams,
+) -> Result<UncheckedExtrinsic<IndracoreNodeRuntime>, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let call = client.encode(call)?;
+    sign_encoded(call, signer, params).await
+}
+
+/// like `sign`, for a call that is already SCALE encoded
+pub(crate) async fn sign_encoded<S>(
+    call: Encoded,
+    signer: &S,
+    params: &SigningParams,
+) -> Result<UncheckedExtrinsic<IndracoreNodeRuntime>, Error>
+where
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let payload = match SignedPayload::<IndracoreNodeRuntime>::new(call, params.extra()) {
+        Ok(payload) => payload,
+        Err(e) => return Err(Error::Other(format!("{:?}", e))),
+    };
+    match signer.sign(payload).await {
+        Ok(extrinsic) => Ok(extrinsic),
+        Err(e) => Err(Error::Other(e)),
+    }
+}
+
+/// The status of an extrinsic in the transaction pool, as sent by
+/// `author_submitAndWatchExtrinsic`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionStatus {
+    Future,
+    Ready,
+    Broadcast(Vec<String>),
+    InBlock(H256),
+    Retracted(H256),
+    FinalityTimeout(H256),
+    Finalized(H256),
+    Usurped(H256),
+    Dropped,
+    Invalid,
+}
+
+impl TransactionStatus {
+    /// whether the node sends no further status after this one
+    pub fn is_final(&self) -> bool {
+        match self {
+            TransactionStatus::Finalized(_)
+            | TransactionStatus::FinalityTimeout(_)
+            | TransactionStatus::Usurped(_)
+            | TransactionStatus::Dropped
+            | TransactionStatus::Invalid => true,
+            _ => false,
+        }
+    }
+}
+
+/// The pool statuses of a submitted extrinsic, ending after a final status.
+pub struct StatusStream {
+    extrinsic_hash: H256,
+    sub: Subscription<TransactionStatus>,
+    done: bool,
+    _rpc: jsonrpsee::Client,
+}
+
+impl StatusStream {
+    pub fn extrinsic_hash(&self) -> H256 {
+        self.extrinsic_hash
+    }
+
+    /// wait for the next status, `None` once a final status was returned
+    pub async fn next(&mut self) -> Option<TransactionStatus> {
+        if self.done {
+            return None;
+        }
+        let status = self.sub.next().await;
+        self.done = status.is_final();
+        Some(status)
+    }
+}
+
+/// Submit `extrinsic` and follow its status in the transaction pool.
+pub async fn submit(
+    extrinsic: UncheckedExtrinsic<IndracoreNodeRuntime>,
+) -> Result<StatusStream, Error> {
+    submit_encoded(extrinsic.encode()).await
+}
+
+/// like `submit`, for an extrinsic that is already SCALE encoded
+pub async fn submit_encoded(encoded: Vec<u8>) -> Result<StatusStream, Error> {
+    let extrinsic_hash = BlakeTwo256::hash(&encoded);
+
+    let rpc = rpc::client().await?;
+    let params = Params::Array(vec![rpc::json(Bytes(encoded))?]);
+    let sub = match rpc
+        .subscribe(
+            "author_submitAndWatchExtrinsic",
+            params,
+            "author_unwatchExtrinsic",
+        )
+        .await
+    {
+        Ok(sub) => sub,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(StatusStream {
+        extrinsic_hash,
+        sub,
+        done: false,
+        _rpc: rpc,
+    })
+}
+
+/// An extrinsic included in a block, with the events it emitted.
+#[derive(Debug)]
+pub struct Included {
+    pub block_hash: H256,
+    pub extrinsic_hash: H256,
+    pub extrinsic_index: u32,
+    pub events: Vec<RawEvent>,
+}
+
+/// Submit `extrinsic` and wait until it is in a block or finalized, as set by
+/// `options`, failing once `options.timeout` elapses.
+pub async fn submit_and_watch(
+    client: &primitives::Client,
+    extrinsic: UncheckedExtrinsic<IndracoreNodeRuntime>,
+    decoder: EventsDecoder<IndracoreNodeRuntime>,
+    options: &TxOptions,
+) -> Result<Included, Error> {
+    submit_and_watch_encoded(client, extrinsic.encode(), decoder, options).await
+}
+
+/// like `submit_and_watch`, for an extrinsic that is already SCALE encoded
+pub async fn submit_and_watch_encoded(
+    client: &primitives::Client,
+    encoded: Vec<u8>,
+    decoder: EventsDecoder<IndracoreNodeRuntime>,
+    options: &TxOptions,
+) -> Result<Included, Error> {
+    let (block_hash, extrinsic_hash, extrinsic_index) =
+        submit_and_wait_encoded(client, encoded, options).await?;
+    let rpc = rpc::client().await?;
+    let events = extrinsic_events(&rpc, &decoder, block_hash, extrinsic_index).await?;
+    Ok(Included {
+        block_hash,
+        extrinsic_hash,
+        extrinsic_index,
+        events,
+    })
+}
+
+/// like `submit_and_watch_encoded`, without reading the events, returns the
+/// block hash, extrinsic hash and index of the extrinsic once it is included
+pub(crate) async fn submit_and_wait_encoded(
+    client: &primitives::Client,
+    encoded: Vec<u8>,
+    options: &TxOptions,
+) -> Result<(H256, H256, u32), Error> {
+    let wait = options.timeout;
+    let deadline = Instant::now() + wait;
+    let mut stream = submit_encoded(encoded).await?;
+    let extrinsic_hash = stream.extrinsic_hash();
+
+    let block_hash = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let status = match timeout(remaining, stream.next()).await {
+            Ok(Some(status)) => status,
+            Ok(None) => {
+                return Err(Error::Other(format!(
+                    "Status stream of extrinsic {:?} ended",
+                    extrinsic_hash
+                )))
+            }
+            Err(_) => {
+                return Err(Error::Other(format!(
+                    "Extrinsic {:?} not included after {:?}",
+                    extrinsic_hash, wait
+                )))
+            }
+        };
+        if let Some(on_status) = &options.on_status {
+            // the receiver may be gone, watching goes on regardless
+            let _ = on_status.unbounded_send(status.clone());
+        }
+        match status {
+            TransactionStatus::InBlock(hash) if options.wait_for == WaitFor::InBlock => break hash,
+            TransactionStatus::Finalized(hash) => break hash,
+            TransactionStatus::FinalityTimeout(_)
+            | TransactionStatus::Usurped(_)
+            | TransactionStatus::Dropped
+            | TransactionStatus::Invalid => {
+                return Err(Error::Other(format!(
+                    "Extrinsic {:?} was not included: {:?}",
+                    extrinsic_hash, status
+                )))
+            }
+            _ => continue,
+        }
+    };
+
+    let extrinsic_index = extrinsic_index(client, block_hash, extrinsic_hash).await?;
+    Ok((block_hash, extrinsic_hash, extrinsic_index))
+}
+
+/// Sign `call` as set by `options`, submit it and wait for it like `submit_and_watch`.
+pub async fn watch<C, S>(
+    client: &primitives::Client,
+    call: C,
+    signer: &S,
+    options: &TxOptions,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let decoder = client.events_decoder::<C>();
+    let extrinsic = create_signed(client, call, signer, options).await?;
+    let included = submit_and_watch(client, extrinsic, decoder, options).await?;
+    Ok(ExtrinsicSuccess {
+        block: included.block_hash,
+        extrinsic: included.extrinsic_hash,
+        events: included.events,
+    })
+}
+
+/// The number of block `hash`.
+pub(crate) async fn block_number(client: &primitives::Client, hash: H256) -> Result<u32, Error> {
+    match client.header(Some(hash)).await? {
+        Some(header) => Ok(*header.number()),
+        None => Err(Error::Other(format!("Block {:?} not found", hash))),
+    }
+}
+
+/// The position of the extrinsic with hash `extrinsic_hash` in block `block_hash`.
+pub async fn extrinsic_index(
+    client: &primitives::Client,
+    block_hash: H256,
+    extrinsic_hash: H256,
+) -> Result<u32, Error> {
+    match find_extrinsic(client, block_hash, extrinsic_hash).await? {
+        Some(index) => Ok(index),
+        None => Err(Error::Other(format!(
+            "Extrinsic {:?} not found in block {:?}",
+            extrinsic_hash, block_hash
+        ))),
+    }
+}
+
+/// like `extrinsic_index`, `None` if the block does not contain the extrinsic
+pub async fn find_extrinsic(
+    client: &primitives::Client,
+    block_hash: H256,
+    extrinsic_hash: H256,
+) -> Result<Option<u32>, Error> {
+    let block = match client.block(Some(block_hash)).await? {
+        Some(block) => block,
+        None => return Err(Error::Other(format!("Block {:?} not found", block_hash))),
+    };
+    Ok(block
+        .block
+        .extrinsics
+        .iter()
+        .position(|ext| BlakeTwo256::hash_of(ext) == extrinsic_hash)
+        .map(|index| index as u32))
+}
+
+/// The events emitted by the extrinsic at `index` in block `block_hash`.
+/// Fails with the runtime error if the extrinsic failed.
+pub async fn extrinsic_events(
+    rpc: &jsonrpsee::Client,
+    decoder: &EventsDecoder<IndracoreNodeRuntime>,
+    block_hash: H256,
+    index: u32,
+) -> Result<Vec<RawEvent>, Error> {
+    let data = match rpc::storage_value(rpc, "System", "Events", block_hash).await? {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut events = Vec::new();
+    for (phase, raw) in decoder.decode_events(&mut &data.0[..])? {
+        if phase != Phase::ApplyExtrinsic(index) {
+            continue;
+        }
+        match raw {
+            Raw::Event(event) => events.push(event),
+            Raw::Error(err) => return Err(err.into()),
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extrinsic::TransactionStatus;
+
+    #[test]
+    fn test_transaction_status() {
+        let status: TransactionStatus = serde_json::from_str(r#""ready""#).unwrap();
+        assert_eq!(status, TransactionStatus::Ready);
+        assert!(!status.is_final());
+
+        let status: TransactionStatus = serde_json::from_str(
+            r#"{"inBlock":"0x40f8c7c624d1d8fbd0873a381c63a0858b4d75315bd8ca62e0111068bbf138e3"}"#,
+        )
+        .unwrap();
+        assert!(matches!(status, TransactionStatus::InBlock(_)));
+        assert!(!status.is_final());
+
+        let status: TransactionStatus =
+            serde_json::from_str(r#"{"broadcast":["12D3KooW"]}"#).unwrap();
+        assert_eq!(
+            status,
+            TransactionStatus::Broadcast(vec!["12D3KooW".into()])
+        );
+
+        let status: TransactionStatus = serde_json::from_str(r#""dropped""#).unwrap();
+        assert!(status.is_final());
+    }
+}
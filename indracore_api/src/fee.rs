@@ -0,0 +1,141 @@
+use crate::{
+    extrinsic::{self, TxOptions},
+    primitives, rpc, util,
+};
+use substrate_subxt::{
+    sp_core::{sr25519, Encode, Pair},
+    Call, ClientBuilder, Error, IndracoreNodeRuntime, PairSigner, Signer,
+};
+
+pub use crate::rpc::DispatchClass;
+
+/// What an extrinsic is expected to cost, as reported by `payment_queryInfo`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeEstimate {
+    pub weight: u64,
+    pub class: DispatchClass,
+    /// fee in plancks, without tip
+    pub partial_fee: u128,
+    /// fee in token units, e.g. `0.0125 SEL`
+    pub formatted: String,
+}
+
+/// Part of an estimated fee kept back on top of it, as the fee multiplier can
+/// rise between the estimate and the inclusion of the extrinsic.
+const MARGIN_DIVISOR: u128 = 10;
+
+/// `fee` with a margin for a rising fee multiplier
+pub(crate) fn with_margin(fee: u128) -> u128 {
+    fee.saturating_add(fee / MARGIN_DIVISOR)
+}
+
+/// Estimate the fee of `call` against the best block.
+///
+/// The call is signed with a throwaway sr25519 key at nonce 0, the fee only
+/// depends on the extrinsic weight and length so the real signer is not needed.
+/// The estimate ignores the nonce and signature length of the real signer, so
+/// it can be slightly low for large nonces or ecdsa signatures. Use
+/// `estimate_signed` where being low loses funds.
+pub async fn estimate<C>(call: C) -> Result<FeeEstimate, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+{
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+
+    let mut signer =
+        PairSigner::<IndracoreNodeRuntime, sr25519::Pair>::new(sr25519::Pair::from_seed(&[0; 32]));
+    signer.set_nonce(0);
+    let extrinsic = client.create_signed(call, &signer).await?;
+
+    estimate_encoded(&extrinsic.encode()).await
+}
+
+/// Estimate the fee of `call` against the best block, signed by `signer` with
+/// the era, tip and nonce of `options` exactly as it would be submitted, so the
+/// length fee matches the real extrinsic. External signers are asked for one
+/// more signature. The fee multiplier can still change before inclusion.
+pub async fn estimate_signed<C, S>(
+    client: &primitives::Client,
+    call: C,
+    signer: &S,
+    options: &TxOptions,
+) -> Result<FeeEstimate, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let extrinsic = extrinsic::create_signed(client, call, signer, options).await?;
+    estimate_encoded(&extrinsic.encode()).await
+}
+
+async fn estimate_encoded(encoded: &[u8]) -> Result<FeeEstimate, Error> {
+    let rpc = rpc::client().await?;
+    let info = rpc::payment_query_info(&rpc, encoded, None).await?;
+    Ok(FeeEstimate {
+        weight: info.weight,
+        class: info.class,
+        partial_fee: info.partial_fee,
+        formatted: format!(
+            "{} {}",
+            util::format_units(info.partial_fee, primitives::decimals()),
+            primitives::symbol()
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        extrinsic::{sign_encoded, SigningParams},
+        fee::with_margin,
+    };
+    use substrate_subxt::{
+        sp_core::{sr25519, Encode, Pair},
+        sp_runtime::generic::Era,
+        Encoded, IndracoreNodeRuntime, PairSigner,
+    };
+
+    fn signed_len(nonce: u32) -> usize {
+        let signer = PairSigner::<IndracoreNodeRuntime, sr25519::Pair>::new(
+            sr25519::Pair::from_string("//Alice", None).unwrap(),
+        );
+        let params = SigningParams {
+            spec_version: 1,
+            transaction_version: 1,
+            genesis_hash: Default::default(),
+            era: Era::Immortal,
+            block_hash: Default::default(),
+            nonce,
+            tip: 0,
+        };
+        // system.remark(0x)
+        let call = Encoded(vec![0, 1, 0]);
+        async_std::task::block_on(sign_encoded(call, &signer, &params))
+            .unwrap()
+            .encode()
+            .len()
+    }
+
+    #[test]
+    fn test_length_grows_with_nonce() {
+        // an estimate signed at nonce 0 is one byte short from nonce 64 on,
+        // which is why `estimate_signed` signs with the real nonce
+        assert_eq!(signed_len(63), signed_len(0));
+        assert_eq!(signed_len(64), signed_len(0) + 1);
+        assert_eq!(signed_len(1 << 14), signed_len(0) + 3);
+    }
+
+    #[test]
+    fn test_with_margin() {
+        assert_eq!(with_margin(1_000), 1_100);
+        assert_eq!(with_margin(9), 9);
+        assert_eq!(with_margin(u128::max_value()), u128::max_value());
+    }
+}
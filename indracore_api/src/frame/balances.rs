@@ -0,0 +1,27 @@
+//! Balances calls that substrate-subxt does not define.
+
+use scale::Encode;
+use substrate_subxt::{
+    balances::{Balances, BalancesEventsDecoder},
+    system::System,
+    Call,
+};
+
+const MODULE: &str = "Balances";
+
+/// Transfer that fails instead of reaping the sender account.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct TransferKeepAliveCall<'a, T: Balances> {
+    pub to: &'a <T as System>::Address,
+    #[codec(compact)]
+    pub amount: T::Balance,
+}
+
+/// Transfer from any account, only dispatchable by root.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct ForceTransferCall<'a, T: Balances> {
+    pub source: &'a <T as System>::Address,
+    pub dest: &'a <T as System>::Address,
+    #[codec(compact)]
+    pub value: T::Balance,
+}
@@ -0,0 +1,63 @@
+use scale::{Decode, Encode};
+use substrate_subxt::{
+    balances::BalancesEventsDecoder, contracts::ContractsEventsDecoder,
+    sp_core::crypto::AccountId32, EventsDecoder, IndracoreNodeRuntime,
+};
+
+use crate::frame::{indices::IndicesEventsDecoder, proxy::ProxyEventsDecoder};
+
+/// A nominator of a validator and its stake, as in `pallet_staking`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Encode, Decode)]
+struct IndividualExposure {
+    who: AccountId32,
+    #[codec(compact)]
+    value: u128,
+}
+
+/// The stake backing a validator, as in `pallet_staking`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Encode, Decode)]
+struct Exposure {
+    #[codec(compact)]
+    total: u128,
+    #[codec(compact)]
+    own: u128,
+    others: Vec<IndividualExposure>,
+}
+
+/// Register the event argument types of every module of the indracore runtime,
+/// including the modules the crate has no calls for, e.g. session, staking,
+/// grandpa and im_online. Without them a block with one of their events cannot
+/// be decoded at all.
+pub fn with_runtime_types(decoder: &mut EventsDecoder<IndracoreNodeRuntime>) {
+    decoder.with_balances();
+    decoder.with_contracts();
+    decoder.with_indices();
+    decoder.with_proxy();
+
+    // plain indices and counters
+    decoder.register_type_size::<u16>("u16");
+    decoder.register_type_size::<u128>("u128");
+    decoder.register_type_size::<u32>("SessionIndex");
+    decoder.register_type_size::<u32>("EraIndex");
+    decoder.register_type_size::<u32>("RegistrarIndex");
+    decoder.register_type_size::<u32>("BountyIndex");
+    decoder.register_type_size::<u32>("ParaId");
+    decoder.register_type_size::<u64>("Weight");
+    // fieldless enums encode to their index
+    decoder.register_type_size::<u8>("ElectionCompute");
+    decoder.register_type_size::<u8>("Status");
+    // hashes and fixed size identifiers
+    decoder.register_type_size::<[u8; 32]>("CallHash");
+    decoder.register_type_size::<[u8; 20]>("EthereumAddress");
+    decoder.register_type_size::<[u8; 8]>("LockIdentifier");
+    decoder.register_type_size::<[u8; 16]>("Kind");
+    decoder.register_type_size::<Vec<u8>>("OpaqueTimeSlot");
+    decoder.register_type_size::<Option<Vec<u8>>>("Option<Vec<u8>>");
+    // block number and extrinsic or task index
+    decoder.register_type_size::<(u32, u32)>("Timepoint<BlockNumber>");
+    decoder.register_type_size::<(u32, u32)>("TaskAddress<BlockNumber>");
+    // grandpa authorities and their weights
+    decoder.register_type_size::<Vec<([u8; 32], u64)>>("AuthorityList");
+    // validators reported offline by im_online
+    decoder.register_type_size::<(AccountId32, Exposure)>("IdentificationTuple");
+}
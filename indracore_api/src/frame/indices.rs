@@ -0,0 +1,54 @@
+use scale::Encode;
+use substrate_subxt::{
+    balances::Balances, module, system::System, Call, Event, IndracoreNodeRuntime, Store,
+};
+
+pub type AccountIndex = u32;
+
+#[module]
+pub trait Indices: System + Balances {
+    #![event_alias(AccountIndex = u32)]
+}
+
+impl Indices for IndracoreNodeRuntime {}
+
+/// Assign a previously unassigned index to the signer.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct ClaimCall<T: Indices> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub index: AccountIndex,
+}
+
+/// Give an index owned by the signer to `new`.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct TransferCall<T: Indices> {
+    pub new: <T as System>::AccountId,
+    pub index: AccountIndex,
+}
+
+/// Free an index owned by the signer and get the deposit back.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct FreeCall<T: Indices> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub index: AccountIndex,
+}
+
+/// Owner, deposit and frozen flag of an assigned index.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct AccountsStore<T: Indices> {
+    #[store(returns = (<T as System>::AccountId, <T as Balances>::Balance, bool))]
+    pub _runtime: std::marker::PhantomData<T>,
+    pub index: AccountIndex,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Event, scale::Decode)]
+pub struct IndexAssignedEvent<T: Indices> {
+    pub who: <T as System>::AccountId,
+    pub index: AccountIndex,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Event, scale::Decode)]
+pub struct IndexFreedEvent<T: Indices> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub index: AccountIndex,
+}
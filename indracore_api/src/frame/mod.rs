@@ -0,0 +1,9 @@
+//! Runtime modules of the indracore chain that are not covered by substrate-subxt.
+
+pub mod balances;
+pub mod events;
+pub mod indices;
+pub mod proxy;
+pub mod system;
+pub mod utility;
+pub mod vesting;
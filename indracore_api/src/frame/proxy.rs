@@ -0,0 +1,94 @@
+use scale::{Decode, Encode};
+use std::marker::PhantomData;
+use substrate_subxt::{
+    balances::Balances, module, system::System, Call, Encoded, IndracoreNodeRuntime, Store,
+};
+
+/// The kinds of proxy the runtime allows an account to register, encoded with
+/// the indices of the runtime's `ProxyType`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Encode, Decode)]
+pub enum ProxyType {
+    Any = 0,
+    NonTransfer = 1,
+    Governance = 2,
+    Staking = 3,
+}
+
+impl Default for ProxyType {
+    fn default() -> Self {
+        ProxyType::Any
+    }
+}
+
+/// A delegate registered as proxy for an account.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct ProxyDefinition<AccountId, BlockNumber> {
+    pub delegate: AccountId,
+    pub proxy_type: ProxyType,
+    pub delay: BlockNumber,
+}
+
+#[module]
+pub trait Proxy: System + Balances {
+    #![event_type(ProxyType)]
+}
+
+impl Proxy for IndracoreNodeRuntime {}
+
+/// Dispatch `call` on behalf of `real`, which must have registered the signer as proxy.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct ProxyCall<'a, T: Proxy> {
+    pub real: <T as System>::AccountId,
+    pub force_proxy_type: Option<ProxyType>,
+    pub call: &'a Encoded,
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct AddProxyCall<T: Proxy> {
+    pub delegate: <T as System>::AccountId,
+    pub proxy_type: ProxyType,
+    pub delay: <T as System>::BlockNumber,
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct RemoveProxyCall<T: Proxy> {
+    pub delegate: <T as System>::AccountId,
+    pub proxy_type: ProxyType,
+    pub delay: <T as System>::BlockNumber,
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct RemoveProxiesCall<T: Proxy> {
+    pub _runtime: PhantomData<T>,
+}
+
+/// The proxies registered for an account together with the deposit held for them.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct ProxiesStore<'a, T: Proxy> {
+    #[store(returns = (
+        Vec<ProxyDefinition<<T as System>::AccountId, <T as System>::BlockNumber>>,
+        <T as Balances>::Balance,
+    ))]
+    pub account_id: &'a <T as System>::AccountId,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::frame::proxy::ProxyType;
+    use scale::{Decode, Encode};
+
+    #[test]
+    fn test_proxy_type_indices() {
+        let types = [
+            (ProxyType::Any, 0u8),
+            (ProxyType::NonTransfer, 1),
+            (ProxyType::Governance, 2),
+            (ProxyType::Staking, 3),
+        ];
+        for (proxy_type, index) in types.iter() {
+            assert_eq!(proxy_type.encode(), vec![*index]);
+            assert_eq!(ProxyType::decode(&mut &[*index][..]).unwrap(), *proxy_type);
+        }
+        assert!(ProxyType::decode(&mut &[4u8][..]).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+//! System calls that substrate-subxt does not define.
+
+use scale::Encode;
+use substrate_subxt::{
+    system::{System, SystemEventsDecoder},
+    Call,
+};
+
+const MODULE: &str = "System";
+
+/// Store nothing but the fact the extrinsic was made, with `remark` in its call data.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct RemarkCall<T: System> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub remark: Vec<u8>,
+}
@@ -0,0 +1,22 @@
+use scale::Encode;
+use substrate_subxt::{module, system::System, Call, Encoded, IndracoreNodeRuntime};
+
+#[module]
+pub trait Utility: System {}
+
+impl Utility for IndracoreNodeRuntime {}
+
+/// Dispatch `calls` in order, stopping at the first failure without reverting
+/// the calls that already succeeded.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct BatchCall<T: Utility> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub calls: Vec<Encoded>,
+}
+
+/// Dispatch `calls` in order, reverting all of them if one fails.
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct BatchAllCall<T: Utility> {
+    pub _runtime: std::marker::PhantomData<T>,
+    pub calls: Vec<Encoded>,
+}
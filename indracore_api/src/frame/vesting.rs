@@ -0,0 +1,21 @@
+use scale::{Decode, Encode};
+use substrate_subxt::{balances::Balances, module, system::System, IndracoreNodeRuntime, Store};
+
+/// A linear vesting schedule: `locked` at `starting_block`, releasing `per_block`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Encode, Decode)]
+pub struct VestingInfo<Balance, BlockNumber> {
+    pub locked: Balance,
+    pub per_block: Balance,
+    pub starting_block: BlockNumber,
+}
+
+#[module]
+pub trait Vesting: System + Balances {}
+
+impl Vesting for IndracoreNodeRuntime {}
+
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct VestingStore<'a, T: Vesting> {
+    #[store(returns = VestingInfo<<T as Balances>::Balance, <T as System>::BlockNumber>)]
+    pub account_id: &'a <T as System>::AccountId,
+}
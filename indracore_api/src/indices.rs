@@ -0,0 +1,132 @@
+use crate::{frame::indices::*, primitives};
+use pallet_indices::address::Address;
+use substrate_subxt::{
+    sp_core::{crypto::AccountId32, H256},
+    ClientBuilder, Error, ExtrinsicSuccess, IndracoreNodeRuntime, Signer,
+};
+
+pub use crate::frame::indices::AccountIndex;
+
+/// The owner of an index, `None` if the index is not assigned.
+pub async fn lookup(index: AccountIndex) -> Result<Option<AccountId32>, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+    lookup_at(&client, index, None).await
+}
+
+/// like `lookup`, in the state of block `at`, the best block if `None`
+pub async fn lookup_at(
+    client: &primitives::Client,
+    index: AccountIndex,
+    at: Option<H256>,
+) -> Result<Option<AccountId32>, Error> {
+    let store = AccountsStore {
+        _runtime: Default::default(),
+        index,
+    };
+    let account = match client.fetch(&store, at).await {
+        Ok(account) => account,
+        Err(e) => return Err(e),
+    };
+    Ok(account.map(|(owner, _deposit, _frozen)| owner))
+}
+
+/// Resolve an address to the account it refers to.
+pub async fn resolve(id: &primitives::IndracoreId) -> Result<AccountId32, Error> {
+    match id {
+        Address::Id(id) => Ok(id.clone()),
+        Address::Index(index) => match lookup(*index).await? {
+            Some(id) => Ok(id),
+            None => Err(Error::Other(format!(
+                "Account index {} is not assigned",
+                index
+            ))),
+        },
+    }
+}
+
+/// claim an unassigned index for the signer
+pub fn claim<S>(
+    signer: &S,
+    index: AccountIndex,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+where
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client
+            .watch(
+                ClaimCall {
+                    _runtime: Default::default(),
+                    index,
+                },
+                signer,
+            )
+            .await
+    })
+}
+
+/// free an index owned by the signer
+pub fn free<S>(
+    signer: &S,
+    index: AccountIndex,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+where
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client
+            .watch(
+                FreeCall {
+                    _runtime: Default::default(),
+                    index,
+                },
+                signer,
+            )
+            .await
+    })
+}
+
+/// transfer an index owned by the signer to `new`
+pub fn transfer<S>(
+    signer: &S,
+    new: AccountId32,
+    index: AccountIndex,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+where
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client.watch(TransferCall { new, index }, signer).await
+    })
+}
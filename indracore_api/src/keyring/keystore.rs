@@ -0,0 +1,165 @@
+use super::{Ed25519, Sr25519};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use substrate_subxt::{
+    sp_core::{crypto::KeyTypeId, ed25519, sr25519, Pair as TraitPair},
+    Error,
+};
+use zeroize::Zeroizing;
+
+pub use substrate_subxt::sp_core::crypto::key_types;
+
+/// A keystore directory laid out like the one of a substrate node: one file per key,
+/// named by the hex key type followed by the hex public key, holding the SURI as a
+/// JSON string.
+pub struct Keystore {
+    path: PathBuf,
+}
+
+impl Keystore {
+    /// open the keystore at `path`, creating the directory if needed
+    pub fn open(path: PathBuf) -> Result<Self, Error> {
+        fs::create_dir_all(&path)?;
+        Ok(Keystore { path })
+    }
+
+    fn key_file_path(&self, key_type: KeyTypeId, public: &[u8]) -> PathBuf {
+        let mut path = self.path.clone();
+        path.push(hex::encode(key_type.0) + hex::encode(public).as_str());
+        path
+    }
+
+    /// store `suri` as the secret of `public`
+    pub fn insert(&self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), Error> {
+        let path = self.key_file_path(key_type, public);
+        let mut file = create_key_file(&path)?;
+        // a file that already existed keeps its mode when opened
+        restrict_permissions(&path)?;
+        if let Err(e) = serde_json::to_writer(&file, suri) {
+            return Err(Error::Other(format!("{:?}", e)));
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// derive the sr25519 public key of `suri` and store it
+    pub fn insert_sr25519(
+        &self,
+        key_type: KeyTypeId,
+        suri: &str,
+        pass: Option<&str>,
+    ) -> Result<sr25519::Public, Error> {
+        let public = match sr25519::Pair::from_string(suri, pass) {
+            Ok(pair) => pair.public(),
+            Err(e) => return Err(Error::Other(format!("{:?}", e))),
+        };
+        self.insert(key_type, suri, public.as_ref())?;
+        Ok(public)
+    }
+
+    /// derive the ed25519 public key of `suri` and store it
+    pub fn insert_ed25519(
+        &self,
+        key_type: KeyTypeId,
+        suri: &str,
+        pass: Option<&str>,
+    ) -> Result<ed25519::Public, Error> {
+        let public = match ed25519::Pair::from_string(suri, pass) {
+            Ok(pair) => pair.public(),
+            Err(e) => return Err(Error::SecretString(e)),
+        };
+        self.insert(key_type, suri, public.as_ref())?;
+        Ok(public)
+    }
+
+    /// the public keys stored for `key_type`
+    pub fn keys(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, Error> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+            match hex::decode(name) {
+                Ok(raw) if raw.len() > 4 && raw[..4] == key_type.0 => keys.push(raw[4..].to_vec()),
+                _ => continue,
+            }
+        }
+        Ok(keys)
+    }
+
+    fn read_suri(&self, key_type: KeyTypeId, public: &[u8]) -> Result<Zeroizing<String>, Error> {
+        let file = File::open(self.key_file_path(key_type, public))?;
+        match serde_json::from_reader::<_, String>(file) {
+            Ok(suri) => Ok(Zeroizing::new(suri)),
+            Err(e) => Err(Error::Other(format!("{:?}", e))),
+        }
+    }
+
+    /// load the sr25519 signer stored for `public`
+    pub fn sr25519(&self, key_type: KeyTypeId, public: &sr25519::Public) -> Result<Sr25519, Error> {
+        let suri = self.read_suri(key_type, public.as_ref())?;
+        Ok(Sr25519::new(suri.as_str()))
+    }
+
+    /// load the ed25519 signer stored for `public`
+    pub fn ed25519(&self, key_type: KeyTypeId, public: &ed25519::Public) -> Result<Ed25519, Error> {
+        let suri = self.read_suri(key_type, public.as_ref())?;
+        Ok(Ed25519::new(suri.as_str()))
+    }
+}
+
+/// create the key file readable by the owner only, before the secret is written
+fn create_key_file(path: &Path) -> Result<File, Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    Ok(options.open(path)?)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keyring::keystore::{key_types, Keystore};
+    use substrate_subxt::sp_core::crypto::AccountId32;
+
+    #[test]
+    fn test_keystore() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("indracore-keystore-{}", std::process::id()));
+        let keystore = Keystore::open(path.clone()).unwrap();
+
+        let suri = "0x0d782a1f150ff7eadd1a4fa0ec3e0a46d77ba89c86ac5d4ce6ddfdc9d54e5beb";
+        let public = keystore
+            .insert_sr25519(key_types::BABE, suri, None)
+            .unwrap();
+
+        let keys = keystore.keys(key_types::BABE).unwrap();
+        assert_eq!(keys, vec![public.as_ref().to_vec()]);
+        assert!(keystore.keys(key_types::GRANDPA).unwrap().is_empty());
+
+        let signer = keystore.sr25519(key_types::BABE, &public).unwrap();
+        assert_eq!(signer.to_accountid().unwrap(), AccountId32::from(public));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
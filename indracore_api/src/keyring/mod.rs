@@ -0,0 +1,314 @@
+pub mod keystore;
+
+use crate::primitives;
+use substrate_subxt::{
+    sp_core::{ed25519, sr25519, Pair as TraitPair},
+    sp_runtime,
+    system::System,
+    Error, IndracoreNodeRuntime, PairSigner,
+};
+
+use std::{fmt, str::FromStr};
+use zeroize::Zeroizing;
+
+/// Parse an SS58 address, or a short account index written as a plain number
+/// or SS58 encoded.
+pub fn indracoreid(pubkey: &str) -> Result<primitives::IndracoreId, Error> {
+    let pubkey = pubkey.trim();
+    if !pubkey.is_empty() && pubkey.bytes().all(|b| b.is_ascii_digit()) {
+        return match pubkey.parse::<u32>() {
+            Ok(index) => Ok(pallet_indices::address::Address::Index(index)),
+            Err(e) => Err(Error::Other(format!("Invalid account index: {:?}", e))),
+        };
+    }
+    if let Some((index, _)) = ss58_index(pubkey) {
+        return Ok(pallet_indices::address::Address::Index(index));
+    }
+    let id = sp_runtime::AccountId32::from_str(pubkey);
+    match id {
+        Ok(id) => Ok(pallet_indices::address::Address::from(id)),
+        Err(e) => return Err(Error::Other(e.into())),
+    }
+}
+
+/// Decode an SS58 encoded account index and its network: a one byte network, the
+/// index in 1, 2, 4 or 8 little endian bytes and a one byte checksum.
+pub(crate) fn ss58_index(address: &str) -> Option<(u32, u8)> {
+    let data = bs58::decode(address).into_vec().ok()?;
+    if data.len() < 3 || data[0] >= 64 {
+        return None;
+    }
+    let (body, checksum) = data.split_at(data.len() - 1);
+    let index = &body[1..];
+    if ![1, 2, 4, 8].contains(&index.len()) {
+        return None;
+    }
+    let mut hasher = blake2_rfc::blake2b::Blake2b::new(64);
+    hasher.update(b"SS58PRE");
+    hasher.update(body);
+    if hasher.finalize().as_bytes()[0] != checksum[0] {
+        return None;
+    }
+    let mut raw = [0u8; 8];
+    raw[..index.len()].copy_from_slice(index);
+    let index = u64::from_le_bytes(raw);
+    if index > u32::max_value() as u64 {
+        return None;
+    }
+    Some((index as u32, data[0]))
+}
+
+pub fn accounid32(pubkey: &str) -> Result<sp_core::crypto::AccountId32, Error> {
+    let id = sp_runtime::AccountId32::from_str(pubkey);
+    match id {
+        Ok(id) => Ok(id),
+        Err(e) => return Err(Error::Other(e.into())),
+    }
+}
+
+/// The secret a keyring type signs with. The phrase is wiped from memory on drop,
+/// the derived keypairs zero their secret key material themselves.
+enum Secret<P> {
+    Suri(Zeroizing<String>),
+    Pair(P),
+}
+
+impl<P: TraitPair> Secret<P> {
+    fn pair(&self, pass: Option<&str>) -> Result<P, Error> {
+        match self {
+            Secret::Suri(suri) => match P::from_string(suri, pass) {
+                Ok(p) => Ok(p),
+                Err(e) => Err(Error::Other(format!("{:?}", e))),
+            },
+            Secret::Pair(p) => Ok(p.clone()),
+        }
+    }
+
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Secret::Suri(a), Secret::Suri(b)) => a == b,
+            (Secret::Pair(a), Secret::Pair(b)) => a.public() == b.public(),
+            _ => false,
+        }
+    }
+}
+
+pub struct Sr25519 {
+    secret: Secret<sr25519::Pair>,
+}
+
+impl Sr25519 {
+    pub fn new(suri: impl Into<String>) -> Self {
+        Sr25519 {
+            secret: Secret::Suri(Zeroizing::new(suri.into())),
+        }
+    }
+
+    /// derive the keypair from `suri` and keep only the keypair, not the phrase
+    pub fn from_suri(suri: &str, pass: Option<&str>) -> Result<Self, Error> {
+        match sr25519::Pair::from_string(suri, pass) {
+            Ok(p) => Ok(Self::from_pair(p)),
+            Err(e) => Err(Error::Other(format!("{:?}", e))),
+        }
+    }
+
+    pub fn from_pair(pair: sr25519::Pair) -> Self {
+        Sr25519 {
+            secret: Secret::Pair(pair),
+        }
+    }
+
+    /// `pass` is ignored if only the keypair is kept
+    pub fn pair(&self, pass: Option<&str>) -> Result<primitives::Sr25519, Error> {
+        let pair = self.secret.pair(pass)?;
+        Ok(PairSigner::<IndracoreNodeRuntime, sr25519::Pair>::new(pair))
+    }
+
+    pub fn to_accountid(&self) -> Result<sp_core::crypto::AccountId32, Error> {
+        let pair = self.secret.pair(None)?;
+        Ok(sp_core::crypto::AccountId32::from(pair.public()))
+    }
+}
+
+impl PartialEq for Sr25519 {
+    fn eq(&self, other: &Self) -> bool {
+        self.secret.eq(&other.secret)
+    }
+}
+
+impl fmt::Debug for Sr25519 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sr25519 { .. }")
+    }
+}
+
+pub struct Ed25519 {
+    secret: Secret<ed25519::Pair>,
+}
+
+impl Ed25519 {
+    pub fn new(suri: impl Into<String>) -> Self {
+        Ed25519 {
+            secret: Secret::Suri(Zeroizing::new(suri.into())),
+        }
+    }
+
+    /// derive the keypair from `suri` and keep only the keypair, not the phrase
+    pub fn from_suri(suri: &str, pass: Option<&str>) -> Result<Self, Error> {
+        match ed25519::Pair::from_string(suri, pass) {
+            Ok(p) => Ok(Self::from_pair(p)),
+            Err(e) => Err(Error::SecretString(e)),
+        }
+    }
+
+    pub fn from_pair(pair: ed25519::Pair) -> Self {
+        Ed25519 {
+            secret: Secret::Pair(pair),
+        }
+    }
+
+    /// `pass` is ignored if only the keypair is kept
+    pub fn pair(&self, pass: Option<&str>) -> Result<primitives::Ed25519, Error> {
+        let pair = self.secret.pair(pass)?;
+        Ok(PairSigner::<IndracoreNodeRuntime, ed25519::Pair>::new(pair))
+    }
+
+    pub fn to_accountid(&self) -> Result<sp_core::crypto::AccountId32, Error> {
+        match self.secret.pair(None) {
+            Ok(data) => Ok(sp_core::crypto::AccountId32::from(data.public())),
+            Err(_) => Err(Error::Other("Invalid account".into())),
+        }
+    }
+}
+
+impl PartialEq for Ed25519 {
+    fn eq(&self, other: &Self) -> bool {
+        self.secret.eq(&other.secret)
+    }
+}
+
+impl fmt::Debug for Ed25519 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Ed25519 { .. }")
+    }
+}
+
+pub fn parse_code_hash(
+    input: &str,
+) -> Result<<IndracoreNodeRuntime as System>::Hash, hex::FromHexError> {
+    let bytes = if input.starts_with("0x") {
+        hex::decode(input.trim_start_matches("0x"))?
+    } else {
+        hex::decode(input)?
+    };
+    if bytes.len() != 32 {
+        return Err(hex::FromHexError::InvalidStringLength);
+    }
+    let mut arr = [0u8; 32];
+    arr.copy_from_slice(&bytes);
+    Ok(arr.into())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keyring::{accounid32, indracoreid, parse_code_hash, ss58_index, Ed25519, Sr25519};
+
+    /// SS58 encode `index` in `bytes` little endian bytes for `network`
+    fn encode_index(index: u64, bytes: usize, network: u8) -> String {
+        let mut data = vec![network];
+        data.extend_from_slice(&index.to_le_bytes()[..bytes]);
+        let mut hasher = blake2_rfc::blake2b::Blake2b::new(64);
+        hasher.update(b"SS58PRE");
+        hasher.update(&data);
+        data.push(hasher.finalize().as_bytes()[0]);
+        bs58::encode(data).into_string()
+    }
+
+    #[test]
+    fn test_sr25519() {
+        let sig =
+            Sr25519::new("0x0d782a1f150ff7eadd1a4fa0ec3e0a46d77ba89c86ac5d4ce6ddfdc9d54e5beb");
+        assert!(sig.pair(None).is_ok());
+        assert!(sig.to_accountid().is_ok())
+    }
+
+    #[test]
+    fn test_keep_pair_only() {
+        let suri = "0x0d782a1f150ff7eadd1a4fa0ec3e0a46d77ba89c86ac5d4ce6ddfdc9d54e5beb";
+        let sr = Sr25519::from_suri(suri, None).unwrap();
+        assert_eq!(
+            sr.to_accountid().unwrap(),
+            Sr25519::new(suri).to_accountid().unwrap()
+        );
+        let ed = Ed25519::from_suri(suri, None).unwrap();
+        assert_eq!(
+            ed.to_accountid().unwrap(),
+            Ed25519::new(suri).to_accountid().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let suri = "0x0d782a1f150ff7eadd1a4fa0ec3e0a46d77ba89c86ac5d4ce6ddfdc9d54e5beb";
+        assert!(!format!("{:?}", Sr25519::new(suri)).contains(suri));
+        assert!(!format!("{:?}", Ed25519::new(suri)).contains(suri));
+    }
+    #[test]
+    fn test_ed25519() {
+        let sig =
+            Ed25519::new("0x0d782a1f150ff7eadd1a4fa0ec3e0a46d77ba89c86ac5d4ce6ddfdc9d54e5beb");
+
+        assert!(sig.pair(None).is_ok());
+        assert!(sig.to_accountid().is_ok())
+    }
+
+    #[test]
+    fn parse_code_hash_works() {
+        // with 0x prefix
+        assert!(parse_code_hash(
+            "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        )
+        .is_ok());
+        // without 0x prefix
+        assert!(
+            parse_code_hash("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+                .is_ok()
+        )
+    }
+
+    #[test]
+    fn test_id() {
+        let pubkey = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+        assert!(indracoreid(pubkey).is_ok());
+        assert!(accounid32(pubkey).is_ok())
+    }
+
+    #[test]
+    fn test_index_id() {
+        use pallet_indices::address::Address;
+
+        assert_eq!(indracoreid("42").unwrap(), Address::Index(42));
+        assert!(matches!(
+            indracoreid("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            Ok(Address::Id(_))
+        ));
+        assert!(indracoreid("42abc").is_err());
+        assert!(indracoreid("+42").is_err());
+        assert!(indracoreid("-42").is_err());
+        assert!(indracoreid("4294967296").is_err());
+
+        let ss58 = encode_index(42, 1, 42);
+        assert_eq!(ss58_index(&ss58), Some((42, 42)));
+        assert_eq!(indracoreid(&ss58).unwrap(), Address::Index(42));
+        assert_eq!(
+            indracoreid(&encode_index(70_000, 4, 0)).unwrap(),
+            Address::Index(70_000)
+        );
+        assert_eq!(ss58_index(&encode_index(1 << 32, 8, 42)), None);
+
+        let mut data = bs58::decode(&ss58).into_vec().unwrap();
+        data[1] += 1;
+        assert_eq!(ss58_index(&bs58::encode(data).into_string()), None);
+    }
+}
@@ -1,5 +1,16 @@
 pub mod balance;
 pub mod contract;
+pub mod extrinsic;
+pub mod fee;
+pub mod frame;
+pub mod indices;
 pub mod keyring;
+pub mod nonce;
+pub mod offline;
+pub mod payload;
+pub mod policy;
 pub mod primitives;
+pub mod proxy;
+pub mod rpc;
+pub mod signer;
 pub mod util;
@@ -0,0 +1,75 @@
+use crate::{primitives, rpc};
+use async_std::sync::{Arc, Mutex};
+use std::ops::Range;
+use substrate_subxt::{
+    sp_core::crypto::AccountId32, system::*, ClientBuilder, Error, IndracoreNodeRuntime,
+};
+
+/// Hands out nonces for one account locally, so several extrinsics of the same
+/// signer can be in flight at once. Clones share the same counter.
+#[derive(Clone)]
+pub struct NonceManager {
+    account_id: AccountId32,
+    next: Arc<Mutex<Option<u32>>>,
+}
+
+impl NonceManager {
+    pub fn new(account_id: AccountId32) -> Self {
+        NonceManager {
+            account_id,
+            next: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn account_id(&self) -> &AccountId32 {
+        &self.account_id
+    }
+
+    /// reserve the next nonce, asking the node only if the counter is not synced
+    pub async fn reserve(&self) -> Result<u32, Error> {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => self.fetch().await?,
+        };
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// drop the local counter, the next reservation asks the node again
+    pub async fn invalidate(&self) {
+        *self.next.lock().await = None;
+    }
+
+    /// reset the local counter to the node's view, pending pool transactions included
+    pub async fn resync(&self) -> Result<u32, Error> {
+        let mut next = self.next.lock().await;
+        let nonce = self.fetch().await?;
+        *next = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// the nonces of extrinsics of the account waiting in the pool of the node
+    pub async fn pending(&self) -> Result<Range<u32>, Error> {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        let on_chain = client.account(&self.account_id, None).await?.nonce;
+        Ok(on_chain..self.fetch().await?)
+    }
+
+    async fn fetch(&self) -> Result<u32, Error> {
+        let client = rpc::client().await?;
+        rpc::request(
+            &client,
+            "system_accountNextIndex",
+            vec![rpc::json(&self.account_id)?],
+        )
+        .await
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    balance::units::Balance,
+    extrinsic::{self, Included, SigningParams, TxOptions},
+    primitives, rpc, util,
+};
+use frame_metadata::RuntimeMetadataPrefixed;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use substrate_subxt::{
+    contracts::ContractsEventsDecoder,
+    sp_core::{crypto::AccountId32, Bytes, Decode, Encode, H256},
+    sp_runtime::generic::Era,
+    Call, ClientBuilder, Error, EventsDecoder, IndracoreNodeRuntime, Metadata, SignedPayload,
+    Signer,
+};
+
+/// Everything a machine without network access needs to sign an extrinsic,
+/// gathered on an online machine with `fetch_params`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineParams {
+    pub nonce: u32,
+    pub genesis_hash: H256,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    /// SCALE encoded runtime metadata in hex, as returned by `state_getMetadata`
+    pub metadata: String,
+    #[serde(default)]
+    pub tip: Balance,
+}
+
+/// collect the signing parameters of `account_id` from the node
+pub async fn fetch_params(account_id: &AccountId32) -> Result<OfflineParams, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+
+    let rpc = rpc::client().await?;
+    let version = rpc::runtime_version(&rpc).await?;
+    let nonce = rpc::request(
+        &rpc,
+        "system_accountNextIndex",
+        vec![rpc::json(account_id)?],
+    )
+    .await?;
+    let metadata: Bytes = rpc::request(&rpc, "state_getMetadata", vec![]).await?;
+
+    Ok(OfflineParams {
+        nonce,
+        genesis_hash: *client.genesis(),
+        spec_version: version.spec_version,
+        transaction_version: version.transaction_version,
+        metadata: format!("0x{}", hex::encode(&metadata.0)),
+        tip: Balance::default(),
+    })
+}
+
+/// Sign `call` without a connection to the node and return the hex encoded
+/// extrinsic. The extrinsic is immortal, it can be submitted any time until
+/// the nonce is used or the runtime is upgraded.
+pub fn sign<C, S>(call: C, signer: &S, params: &OfflineParams) -> Result<String, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+    S: Signer<IndracoreNodeRuntime> + Send + Sync,
+{
+    let metadata = decode_metadata(&params.metadata)?;
+    let call = metadata
+        .module_with_calls(C::MODULE)
+        .and_then(|module| module.call(C::FUNCTION, call))?;
+
+    let extra = SigningParams {
+        spec_version: params.spec_version,
+        transaction_version: params.transaction_version,
+        genesis_hash: params.genesis_hash,
+        era: Era::Immortal,
+        block_hash: params.genesis_hash,
+        nonce: params.nonce,
+        tip: params.tip.plancks(),
+    }
+    .extra();
+    let payload = match SignedPayload::<IndracoreNodeRuntime>::new(call, extra) {
+        Ok(payload) => payload,
+        Err(e) => return Err(Error::Other(format!("{:?}", e))),
+    };
+    let extrinsic = match async_std::task::block_on(signer.sign(payload)) {
+        Ok(extrinsic) => extrinsic,
+        Err(e) => return Err(Error::Other(e)),
+    };
+    Ok(format!("0x{}", hex::encode(extrinsic.encode())))
+}
+
+/// Submit an extrinsic signed with `sign` and wait for it as set by `options`.
+/// Only the events of the system, balances and contracts modules can be decoded.
+pub fn submit(extrinsic: &str, options: &TxOptions) -> Result<Included, Error> {
+    let encoded = match util::decode_hex(extrinsic) {
+        Ok(bytes) => bytes,
+        Err(e) => return Err(Error::Other(format!("{:?}", e))),
+    };
+
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let mut decoder = EventsDecoder::<IndracoreNodeRuntime>::new(client.metadata().clone());
+        decoder.with_contracts();
+        extrinsic::submit_and_watch_encoded(&client, encoded, decoder, options).await
+    })
+}
+
+fn decode_metadata(metadata: &str) -> Result<Metadata, Error> {
+    let bytes = match util::decode_hex(metadata) {
+        Ok(bytes) => bytes,
+        Err(e) => return Err(Error::Other(format!("{:?}", e))),
+    };
+    let prefixed = RuntimeMetadataPrefixed::decode(&mut &bytes[..])?;
+    Ok(Metadata::try_from(prefixed)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::offline::OfflineParams;
+
+    #[test]
+    fn test_params_json() {
+        let params: OfflineParams = serde_json::from_str(
+            r#"{
+                "nonce": 3,
+                "genesisHash": "0x40f8c7c624d1d8fbd0873a381c63a0858b4d75315bd8ca62e0111068bbf138e3",
+                "specVersion": 1,
+                "transactionVersion": 1,
+                "metadata": "0x6d657461"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(params.nonce, 3);
+        assert_eq!(params.tip, 0.into());
+
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(
+            serde_json::from_str::<OfflineParams>(&json).unwrap(),
+            params
+        );
+    }
+}
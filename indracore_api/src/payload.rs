@@ -0,0 +1,211 @@
+use crate::{
+    balance::units::Balance,
+    extrinsic::{self, Included, SigningParams, TxOptions},
+    primitives, util,
+};
+use serde::{Deserialize, Serialize};
+use substrate_subxt::{
+    balances::TransferCall,
+    contracts::ContractsEventsDecoder,
+    sp_core::{crypto::AccountId32, Decode, Encode, H256},
+    sp_runtime::{generic::Era, traits::Verify, MultiSignature},
+    Call, ClientBuilder, Encoded, Error, EventsDecoder, IndracoreNodeRuntime, SignedPayload,
+    UncheckedExtrinsic,
+};
+
+/// An extrinsic waiting for a signature made on another machine, with every
+/// field it commits to. Byte fields are hex encoded.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedPayload {
+    pub signer: AccountId32,
+    /// SCALE encoded call
+    pub call: String,
+    /// SCALE encoded era
+    pub era: String,
+    pub nonce: u32,
+    pub tip: Balance,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    pub genesis_hash: H256,
+    /// the block the era starts at, the genesis block if immortal
+    pub block_hash: H256,
+    /// the bytes to sign, blake2-256 hashed if longer than 256 bytes
+    pub signing_payload: String,
+}
+
+impl UnsignedPayload {
+    fn signing_params(&self) -> Result<SigningParams, Error> {
+        let era = Era::decode(&mut &decode(&self.era)?[..])?;
+        Ok(SigningParams {
+            spec_version: self.spec_version,
+            transaction_version: self.transaction_version,
+            genesis_hash: self.genesis_hash,
+            era,
+            block_hash: self.block_hash,
+            nonce: self.nonce,
+            tip: self.tip.plancks(),
+        })
+    }
+
+    /// rebuild the signed payload from the fields, ignoring `signing_payload`
+    fn payload(&self) -> Result<SignedPayload<IndracoreNodeRuntime>, Error> {
+        let call = Encoded(decode(&self.call)?);
+        match SignedPayload::<IndracoreNodeRuntime>::new(call, self.signing_params()?.extra()) {
+            Ok(payload) => Ok(payload),
+            Err(e) => Err(Error::Other(format!("{:?}", e))),
+        }
+    }
+
+    /// Attach `signature` and build the extrinsic. Fails if the signature
+    /// is not made by `signer` over the payload.
+    pub fn attach(
+        &self,
+        signature: MultiSignature,
+    ) -> Result<UncheckedExtrinsic<IndracoreNodeRuntime>, Error> {
+        let payload = self.payload()?;
+        let valid = payload.using_encoded(|bytes| signature.verify(bytes, &self.signer));
+        if !valid {
+            return Err(Error::Other(format!(
+                "Signature is not valid for {}",
+                self.signer
+            )));
+        }
+        let (call, extra, _) = payload.deconstruct();
+        Ok(UncheckedExtrinsic::<IndracoreNodeRuntime>::new_signed(
+            call,
+            self.signer.clone().into(),
+            signature,
+            extra,
+        ))
+    }
+}
+
+/// Build the signing payload of `call` for `signer` with the era, tip and nonce of `options`.
+pub fn export<C>(
+    call: C,
+    signer: &AccountId32,
+    options: &TxOptions,
+) -> Result<UnsignedPayload, Error>
+where
+    C: Call<IndracoreNodeRuntime> + Send + Sync,
+{
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let params = extrinsic::signing_params(&client, signer, options.nonce, options).await?;
+        let call = client.encode(call)?;
+        let payload = match SignedPayload::<IndracoreNodeRuntime>::new(call, params.extra()) {
+            Ok(payload) => payload,
+            Err(e) => return Err(Error::Other(format!("{:?}", e))),
+        };
+        let signing_payload = payload.using_encoded(|bytes| format!("0x{}", hex::encode(bytes)));
+        let (call, _, _) = payload.deconstruct();
+
+        Ok(UnsignedPayload {
+            signer: signer.clone(),
+            call: format!("0x{}", hex::encode(&call.0)),
+            era: format!("0x{}", hex::encode(params.era.encode())),
+            nonce: params.nonce,
+            tip: params.tip.into(),
+            spec_version: params.spec_version,
+            transaction_version: params.transaction_version,
+            genesis_hash: params.genesis_hash,
+            block_hash: params.block_hash,
+            signing_payload,
+        })
+    })
+}
+
+/// build the signing payload of a transfer from `signer`, see `export`
+pub fn export_transfer(
+    signer: &AccountId32,
+    reciever: &primitives::IndracoreId,
+    amount: Balance,
+    options: &TxOptions,
+) -> Result<UnsignedPayload, Error> {
+    let call = TransferCall {
+        to: reciever,
+        amount: amount.plancks(),
+    };
+    export(call, signer, options)
+}
+
+/// Attach `signature` to `payload`, submit the extrinsic and wait for it as
+/// set by `options`. Only the events of the system, balances and contracts
+/// modules can be decoded.
+pub fn submit(
+    payload: &UnsignedPayload,
+    signature: MultiSignature,
+    options: &TxOptions,
+) -> Result<Included, Error> {
+    let extrinsic = payload.attach(signature)?;
+
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+
+        let mut decoder = EventsDecoder::<IndracoreNodeRuntime>::new(client.metadata().clone());
+        decoder.with_contracts();
+        extrinsic::submit_and_watch(&client, extrinsic, decoder, options).await
+    })
+}
+
+fn decode(input: &str) -> Result<Vec<u8>, Error> {
+    match util::decode_hex(input) {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::payload::UnsignedPayload;
+    use substrate_subxt::{
+        sp_core::{sr25519, Encode, Pair},
+        sp_runtime::generic::Era,
+    };
+
+    #[test]
+    fn test_attach_checks_signature() {
+        let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+        let bob = sr25519::Pair::from_string("//Bob", None).unwrap();
+        let mut payload = UnsignedPayload {
+            signer: alice.public().into(),
+            // system.remark(0x)
+            call: "0x000100".into(),
+            era: format!("0x{}", hex::encode(Era::Immortal.encode())),
+            nonce: 0,
+            tip: 0.into(),
+            spec_version: 1,
+            transaction_version: 1,
+            genesis_hash: Default::default(),
+            block_hash: Default::default(),
+            signing_payload: String::new(),
+        };
+        let bytes = payload.payload().unwrap().encode();
+        payload.signing_payload = format!("0x{}", hex::encode(&bytes));
+
+        assert!(payload.attach(alice.sign(&bytes).into()).is_ok());
+        assert!(payload.attach(bob.sign(&bytes).into()).is_err());
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(
+            serde_json::from_str::<UnsignedPayload>(&json).unwrap(),
+            payload
+        );
+    }
+}
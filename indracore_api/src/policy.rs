@@ -0,0 +1,913 @@
+use crate::{
+    balance::{
+        batch::{BatchItem, BatchTransfer, ItemStatus},
+        payout::{Payout, PayoutItem},
+        transaction::{self, Transaction, TransferReceipt},
+        units::Balance,
+    },
+    contract::{call::ContarctCall, instantiate::Instantiate},
+    extrinsic::TxOptions,
+    indices,
+    nonce::NonceManager,
+    offline::OfflineParams,
+    payload::{self, UnsignedPayload},
+    primitives,
+    proxy::Proxied,
+    signer::ToSigner,
+};
+use pallet_indices::address::Address;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use substrate_subxt::{
+    contracts::InstantiatedEvent,
+    sp_core::crypto::{AccountId32, Ss58Codec},
+    ClientBuilder, Error, ExtrinsicSuccess, IndracoreNodeRuntime, Signer,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Limits on what a signer can send, checked by a `Guard` before anything is
+/// signed.
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    /// most the signer can send per UTC day, no limit if `None`
+    pub daily_limit: Option<Balance>,
+    /// most the signer can send at once
+    pub max_amount: Option<Balance>,
+    /// if set, the only recipients the signer can send to
+    pub allow: Option<HashSet<AccountId32>>,
+    /// recipients the signer can never send to
+    pub deny: HashSet<AccountId32>,
+    /// amounts above this also need the `Approver` of the guard
+    pub approval_threshold: Option<Balance>,
+}
+
+/// A transfer or contract call value about to be signed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Spend {
+    pub from: AccountId32,
+    pub to: AccountId32,
+    pub amount: Balance,
+    /// what `from` already sent today, `amount` excluded
+    pub spent_today: Balance,
+}
+
+/// Gives the second approval of spends above `Policy::approval_threshold`,
+/// e.g. by asking another person to confirm them.
+pub trait Approver: Send + Sync {
+    fn approve(&self, spend: &Spend) -> bool;
+}
+
+impl<F> Approver for F
+where
+    F: Fn(&Spend) -> bool + Send + Sync,
+{
+    fn approve(&self, spend: &Spend) -> bool {
+        self(spend)
+    }
+}
+
+/// A reason a `Guard` refuses to sign a spend.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// the recipient is in `Policy::deny`
+    Denied(AccountId32),
+    /// the recipient is not in `Policy::allow`
+    NotAllowed(AccountId32),
+    /// the amount is above `Policy::max_amount`
+    AboveMaximum { amount: Balance, maximum: Balance },
+    /// the amount and what was sent today are above `Policy::daily_limit`
+    DailyLimit {
+        spent: Balance,
+        amount: Balance,
+        limit: Balance,
+    },
+    /// the amount is above `Policy::approval_threshold` and was not approved
+    NotApproved { amount: Balance, threshold: Balance },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Denied(to) => write!(f, "Recipient {} is denied", to.to_ss58check()),
+            Violation::NotAllowed(to) => {
+                write!(f, "Recipient {} is not allowed", to.to_ss58check())
+            }
+            Violation::AboveMaximum { amount, maximum } => {
+                write!(f, "Amount {} is above the maximum {}", amount, maximum)
+            }
+            Violation::DailyLimit {
+                spent,
+                amount,
+                limit,
+            } => write!(
+                f,
+                "Amount {} after {} sent today is above the daily limit {}",
+                amount, spent, limit
+            ),
+            Violation::NotApproved { amount, threshold } => write!(
+                f,
+                "Amount {} is above {} and was not approved",
+                amount, threshold
+            ),
+        }
+    }
+}
+
+impl Policy {
+    /// every rule `spend` breaks, `approver` is only asked if no other rule is
+    /// broken
+    pub fn check(&self, spend: &Spend, approver: Option<&dyn Approver>) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if self.deny.contains(&spend.to) {
+            violations.push(Violation::Denied(spend.to.clone()));
+        }
+        if let Some(allow) = &self.allow {
+            if !allow.contains(&spend.to) {
+                violations.push(Violation::NotAllowed(spend.to.clone()));
+            }
+        }
+        if let Some(maximum) = self.max_amount {
+            if spend.amount > maximum {
+                violations.push(Violation::AboveMaximum {
+                    amount: spend.amount,
+                    maximum,
+                });
+            }
+        }
+        if let Some(limit) = self.daily_limit {
+            if exceeds(spend.spent_today, spend.amount, limit) {
+                violations.push(Violation::DailyLimit {
+                    spent: spend.spent_today,
+                    amount: spend.amount,
+                    limit,
+                });
+            }
+        }
+        if let Some(threshold) = self.approval_threshold {
+            if violations.is_empty()
+                && spend.amount > threshold
+                && !approver.map_or(false, |approver| approver.approve(spend))
+            {
+                violations.push(Violation::NotApproved {
+                    amount: spend.amount,
+                    threshold,
+                });
+            }
+        }
+        violations
+    }
+}
+
+/// Checks spends against the `Policy` of the account they are paid from
+/// before signing them, keeping what each account sent per day in a local
+/// SQLite database.
+///
+/// Only spends made through the guard are checked, so it has to be the only
+/// way the signing keys are used. It wraps every way the crate sends funds:
+/// transfers, sweeps, batches, payouts, contract calls and instantiations with
+/// value, and their proxied and offline variants; `Proxied::run` refuses calls
+/// that could send funds. A spend counts towards the daily limit once it is
+/// authorized, even if the extrinsic then fails: a timed out extrinsic may
+/// still be included. Batches and payouts know which transfers were not sent,
+/// those are taken off the counter again.
+pub struct Guard {
+    conn: Connection,
+    policy: Policy,
+    policies: HashMap<AccountId32, Policy>,
+    approver: Option<Box<dyn Approver>>,
+}
+
+impl Guard {
+    /// open the counters at `path`, creating them if needed, `policy` applies
+    /// to every signer without a policy of its own
+    pub fn open<P: AsRef<Path>>(path: P, policy: Policy) -> Result<Self, Error> {
+        let conn = db(Connection::open(path))?;
+        db(conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS spending (
+                account BLOB PRIMARY KEY,
+                day INTEGER NOT NULL,
+                spent TEXT NOT NULL
+            );",
+        ))?;
+        Ok(Guard {
+            conn,
+            policy,
+            policies: HashMap::new(),
+            approver: None,
+        })
+    }
+
+    /// use `policy` for `signer` instead of the default one
+    pub fn with_policy(mut self, signer: AccountId32, policy: Policy) -> Self {
+        self.policies.insert(signer, policy);
+        self
+    }
+
+    /// ask `approver` for spends above the approval threshold, without one
+    /// they are refused
+    pub fn with_approver<A: Approver + 'static>(mut self, approver: A) -> Self {
+        self.approver = Some(Box::new(approver));
+        self
+    }
+
+    /// the policy of `signer`
+    pub fn policy(&self, signer: &AccountId32) -> &Policy {
+        self.policies.get(signer).unwrap_or(&self.policy)
+    }
+
+    /// what `signer` sent today, in UTC
+    pub fn spent_today(&self, signer: &AccountId32) -> Result<Balance, Error> {
+        spent_on(&self.conn, signer, today())
+    }
+
+    /// Check a spend of `amount` from `from` to `to`, and count it towards the
+    /// daily limit of `from` if it is allowed. Fails with every broken rule
+    /// otherwise.
+    pub fn authorize(
+        &mut self,
+        from: &AccountId32,
+        to: &AccountId32,
+        amount: Balance,
+    ) -> Result<(), Error> {
+        self.authorize_on(from, &[(to.clone(), amount)], today())
+    }
+
+    /// like `authorize` for many spends from `from`, every spend is checked on
+    /// its own and their total against the daily limit, none is counted
+    /// unless all are allowed
+    pub fn authorize_all(
+        &mut self,
+        from: &AccountId32,
+        spends: &[(AccountId32, Balance)],
+    ) -> Result<(), Error> {
+        self.authorize_on(from, spends, today())
+    }
+
+    /// `Transaction::run_with_options`, once the guard authorized the transfer.
+    /// Replace a pending transfer with `options.nonce`, it counts again.
+    pub fn transfer<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        self.authorize_transaction(transaction, pass, transaction.amount)?;
+        transaction.run_with_options(pass, options)
+    }
+
+    /// `Transaction::run_keep_alive_with_options`, once the guard authorized
+    /// the transfer
+    pub fn transfer_keep_alive<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        self.authorize_transaction(transaction, pass, transaction.amount)?;
+        transaction.run_keep_alive_with_options(pass, options)
+    }
+
+    /// `Transaction::run_with_memo_with_options`, once the guard authorized the
+    /// transfer
+    pub fn transfer_with_memo<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        memo: &[u8],
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        self.authorize_transaction(transaction, pass, transaction.amount)?;
+        transaction.run_with_memo_with_options(pass, memo, options)
+    }
+
+    /// Authorize the transfer now and return `Transaction::run_with_nonces`,
+    /// so guarded transfers of one sender can still be awaited concurrently.
+    /// The transfer counts even if the returned future fails.
+    pub fn transfer_with_nonces<'a, S: ToSigner>(
+        &mut self,
+        transaction: &'a Transaction<S>,
+        pass: Option<&'a str>,
+        options: &'a TxOptions,
+        nonces: &'a NonceManager,
+    ) -> Result<impl Future<Output = Result<TransferReceipt, Error>> + 'a, Error> {
+        self.authorize_transaction(transaction, pass, transaction.amount)?;
+        Ok(transaction.run_with_nonces(pass, options, nonces))
+    }
+
+    /// `Transaction::sweep_with_options`, once the guard authorized the amount
+    /// left to sweep, which is then transferred even if the balance changed
+    pub fn sweep<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        keep_alive: bool,
+        options: &TxOptions,
+    ) -> Result<TransferReceipt, Error> {
+        let amount = transaction.sweep_amount(pass, keep_alive, options)?;
+        self.authorize_transaction(transaction, pass, amount)?;
+        transaction.transfer_amount(pass, amount, keep_alive, options)
+    }
+
+    /// `Transaction::sign_offline`, once the guard authorized the transfer
+    pub fn sign_offline<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        params: &OfflineParams,
+    ) -> Result<String, Error> {
+        self.authorize_transaction(transaction, pass, transaction.amount)?;
+        transaction.sign_offline(pass, params)
+    }
+
+    /// `payload::export_transfer`, once the guard authorized the transfer from
+    /// `signer`
+    pub fn export_transfer(
+        &mut self,
+        signer: &AccountId32,
+        reciever: &primitives::IndracoreId,
+        amount: Balance,
+        options: &TxOptions,
+    ) -> Result<UnsignedPayload, Error> {
+        let to = async_std::task::block_on(indices::resolve(reciever))?;
+        self.authorize(signer, &to, amount)?;
+        payload::export_transfer(signer, reciever, amount, options)
+    }
+
+    /// `transaction::force_transfer`, once the guard authorized the transfer,
+    /// counted against `source`
+    pub fn force_transfer<S: ToSigner>(
+        &mut self,
+        sudo: &S,
+        pass: Option<&str>,
+        source: &primitives::IndracoreId,
+        dest: &primitives::IndracoreId,
+        amount: Balance,
+    ) -> Result<TransferReceipt, Error> {
+        let accounts = resolve_all(&[source, dest])?;
+        self.authorize(&accounts[0], &accounts[1], amount)?;
+        transaction::force_transfer(sudo, pass, source, dest, amount)
+    }
+
+    /// `Proxied::transfer`, once the guard authorized the transfer, counted
+    /// against the real account
+    pub fn proxied_transfer(
+        &mut self,
+        proxied: &Proxied,
+        reciever: &primitives::IndracoreId,
+        amount: Balance,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let to = async_std::task::block_on(indices::resolve(reciever))?;
+        self.authorize(&proxied.real, &to, amount)?;
+        proxied.transfer(reciever, amount)
+    }
+
+    /// `Proxied::call`, once the guard authorized the value sent to the
+    /// contract, counted against the real account
+    pub fn proxied_call<S: ToSigner>(
+        &mut self,
+        proxied: &Proxied,
+        call: &ContarctCall<S>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        self.authorize_call(&proxied.real, call)?;
+        proxied.call(call)
+    }
+
+    /// `Proxied::instantiate`, once the guard authorized the endowment of the
+    /// contract, counted against the real account
+    pub fn proxied_instantiate<S: ToSigner>(
+        &mut self,
+        proxied: &Proxied,
+        inst: &Instantiate<S>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        self.authorize_instantiate(&proxied.real, inst)?;
+        proxied.instantiate(inst)
+    }
+
+    /// `BatchTransfer::run_with_options`, once the guard authorized every
+    /// transfer of the batch. Transfers that failed or were not executed are
+    /// taken off the counter again, all of them if the batch fails before its
+    /// first chunk is submitted.
+    pub fn batch<S: ToSigner>(
+        &mut self,
+        batch: &BatchTransfer<S>,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<Vec<BatchItem>, Error> {
+        let from = batch.sender.to_signer(pass)?.account_id().clone();
+        let recievers: Vec<_> = batch.transfers.iter().map(|(to, _)| to).collect();
+        let spends: Vec<_> = resolve_all(&recievers)?
+            .into_iter()
+            .zip(batch.transfers.iter().map(|(_, amount)| *amount))
+            .collect();
+        let day = today();
+        self.authorize_on(&from, &spends, day)?;
+        match batch.run_with_options(pass, options) {
+            Ok(items) => {
+                let unsent = items
+                    .iter()
+                    .filter(|item| match item.status {
+                        ItemStatus::Failed(_) | ItemStatus::NotExecuted => true,
+                        ItemStatus::Completed | ItemStatus::Unknown { .. } => false,
+                    })
+                    .map(|item| item.amount);
+                self.refund_on(&from, total(unsent), day)?;
+                Ok(items)
+            }
+            Err(e) => {
+                self.refund_on(&from, total(spends.iter().map(|(_, amount)| *amount)), day)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// `Payout::run`, once the guard authorized every row not paid yet. The
+    /// rows still not paid afterwards, e.g. because the payout was refused or
+    /// a row failed, are taken off the counter again; the payout retries them
+    /// on its next run, and they count again then.
+    pub fn payout<S: ToSigner>(
+        &mut self,
+        payout: &Payout<S>,
+        pass: Option<&str>,
+    ) -> Result<Vec<PayoutItem>, Error> {
+        let from = payout.sender.to_signer(pass)?.account_id().clone();
+        let rows = payout.unpaid()?;
+        let recievers: Vec<_> = rows.iter().map(|row| &row.reciever).collect();
+        let spends: Vec<_> = resolve_all(&recievers)?
+            .into_iter()
+            .zip(rows.iter().map(|row| row.amount))
+            .collect();
+        let day = today();
+        self.authorize_on(&from, &spends, day)?;
+        let result = payout.run(pass);
+
+        // rows that were pending before are not counted now, so they are not
+        // taken off either
+        let authorized: HashSet<usize> = rows.iter().map(|row| row.line).collect();
+        let unsent = payout
+            .unpaid()?
+            .into_iter()
+            .filter(|row| authorized.contains(&row.line))
+            .map(|row| row.amount);
+        self.refund_on(&from, total(unsent), day)?;
+        result
+    }
+
+    /// `ContarctCall::run_with_options`, once the guard authorized the value
+    /// sent to the contract. Calls without value are not checked.
+    pub fn call<S: ToSigner>(
+        &mut self,
+        call: &ContarctCall<S>,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let from = call.signer.to_signer(pass)?.account_id().clone();
+        self.authorize_call(&from, call)?;
+        call.run_with_options(pass, options)
+    }
+
+    /// `ContarctCall::sign_offline`, once the guard authorized the value sent
+    /// to the contract
+    pub fn sign_call_offline<S: ToSigner>(
+        &mut self,
+        call: &ContarctCall<S>,
+        pass: Option<&str>,
+        params: &OfflineParams,
+    ) -> Result<String, Error> {
+        let from = call.signer.to_signer(pass)?.account_id().clone();
+        self.authorize_call(&from, call)?;
+        call.sign_offline(pass, params)
+    }
+
+    /// `Instantiate::instantiate_with_options`, once the guard authorized the
+    /// endowment sent to the address the contract gets. Instantiations without
+    /// endowment are not checked.
+    pub fn instantiate<S: ToSigner>(
+        &mut self,
+        inst: &Instantiate<S>,
+        pass: Option<&str>,
+        options: &TxOptions,
+    ) -> Result<InstantiatedEvent<IndracoreNodeRuntime>, Error> {
+        let from = inst.signer.to_signer(pass)?.account_id().clone();
+        self.authorize_instantiate(&from, inst)?;
+        inst.instantiate_with_options(pass, options)
+    }
+
+    fn authorize_transaction<S: ToSigner>(
+        &mut self,
+        transaction: &Transaction<S>,
+        pass: Option<&str>,
+        amount: Balance,
+    ) -> Result<(), Error> {
+        let from = transaction.sender.to_signer(pass)?.account_id().clone();
+        let to = async_std::task::block_on(indices::resolve(&transaction.reciever))?;
+        self.authorize(&from, &to, amount)
+    }
+
+    fn authorize_call<S: ToSigner>(
+        &mut self,
+        from: &AccountId32,
+        call: &ContarctCall<S>,
+    ) -> Result<(), Error> {
+        if call.value.plancks() == 0 {
+            return Ok(());
+        }
+        let to = async_std::task::block_on(indices::resolve(&call.contract))?;
+        self.authorize(from, &to, call.value)
+    }
+
+    fn authorize_instantiate<S: ToSigner>(
+        &mut self,
+        from: &AccountId32,
+        inst: &Instantiate<S>,
+    ) -> Result<(), Error> {
+        if inst.endowment.plancks() == 0 {
+            return Ok(());
+        }
+        let to = inst.address(from)?;
+        self.authorize(from, &to, inst.endowment)
+    }
+
+    fn authorize_on(
+        &mut self,
+        from: &AccountId32,
+        spends: &[(AccountId32, Balance)],
+        day: u64,
+    ) -> Result<(), Error> {
+        let policy = self.policy(from);
+        let limit = policy.daily_limit;
+        let mut checked = Vec::with_capacity(spends.len());
+        let mut spent_today = spent_on(&self.conn, from, day)?;
+        for (to, amount) in spends {
+            checked.push(Spend {
+                from: from.clone(),
+                to: to.clone(),
+                amount: *amount,
+                spent_today,
+            });
+            spent_today = spent_today
+                .plancks()
+                .saturating_add(amount.plancks())
+                .into();
+        }
+
+        // the approver is only asked once no spend breaks any other rule
+        let violations: Vec<Violation> = checked
+            .iter()
+            .flat_map(|spend| policy.check(spend, None))
+            .filter(|violation| !matches!(violation, Violation::NotApproved { .. }))
+            .collect();
+        if !violations.is_empty() {
+            return Err(refused(&violations));
+        }
+        let approver = self.approver.as_deref();
+        let violations: Vec<Violation> = checked
+            .iter()
+            .flat_map(|spend| policy.check(spend, approver))
+            .collect();
+        if !violations.is_empty() {
+            return Err(refused(&violations));
+        }
+
+        // the approver may have taken a while, another process could have
+        // spent in the meantime
+        let amount = total(spends.iter().map(|(_, amount)| *amount));
+        let tx = db(self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate))?;
+        let spent = spent_on(&tx, from, day)?;
+        if let Some(limit) = limit {
+            if exceeds(spent, amount, limit) {
+                return Err(refused(&[Violation::DailyLimit {
+                    spent,
+                    amount,
+                    limit,
+                }]));
+            }
+        }
+        let spent = spent.plancks().saturating_add(amount.plancks());
+        db(tx.execute(
+            "INSERT OR REPLACE INTO spending (account, day, spent) VALUES (?1, ?2, ?3)",
+            params![AsRef::<[u8]>::as_ref(from), day as i64, spent.to_string()],
+        ))?;
+        db(tx.commit())
+    }
+
+    /// take `amount` that was authorized on `day` but not sent off the counter
+    /// of `from`, a counter of a later day is left alone
+    fn refund_on(&mut self, from: &AccountId32, amount: Balance, day: u64) -> Result<(), Error> {
+        if amount.plancks() == 0 {
+            return Ok(());
+        }
+        let tx = db(self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate))?;
+        let spent = spent_on(&tx, from, day)?
+            .plancks()
+            .saturating_sub(amount.plancks());
+        db(tx.execute(
+            "UPDATE spending SET spent = ?3 WHERE account = ?1 AND day = ?2",
+            params![AsRef::<[u8]>::as_ref(from), day as i64, spent.to_string()],
+        ))?;
+        db(tx.commit())
+    }
+}
+
+/// the sum of `amounts`
+fn total<I: Iterator<Item = Balance>>(amounts: I) -> Balance {
+    amounts
+        .fold(0u128, |sum, amount| sum.saturating_add(amount.plancks()))
+        .into()
+}
+
+/// the accounts of `addresses`, looking indices up with one connection
+fn resolve_all(addresses: &[&primitives::IndracoreId]) -> Result<Vec<AccountId32>, Error> {
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let account = match address {
+                Address::Id(id) => id.clone(),
+                Address::Index(index) => match indices::lookup_at(&client, *index, None).await? {
+                    Some(id) => id,
+                    None => {
+                        return Err(Error::Other(format!(
+                            "Account index {} is not assigned",
+                            index
+                        )))
+                    }
+                },
+            };
+            accounts.push(account);
+        }
+        Ok(accounts)
+    })
+}
+
+/// what `signer` sent on `day`, counted in days since the unix epoch
+fn spent_on(conn: &Connection, signer: &AccountId32, day: u64) -> Result<Balance, Error> {
+    let row: Option<(i64, String)> = db(conn
+        .query_row(
+            "SELECT day, spent FROM spending WHERE account = ?1",
+            params![AsRef::<[u8]>::as_ref(signer)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional())?;
+    match row {
+        Some((stored, spent)) if stored as u64 == day => match spent.parse::<u128>() {
+            Ok(spent) => Ok(spent.into()),
+            Err(e) => Err(Error::Other(format!("Invalid spending counter: {:?}", e))),
+        },
+        _ => Ok(Balance::default()),
+    }
+}
+
+fn exceeds(spent: Balance, amount: Balance, limit: Balance) -> bool {
+    match spent.plancks().checked_add(amount.plancks()) {
+        Some(total) => total > limit.plancks(),
+        None => true,
+    }
+}
+
+fn refused(violations: &[Violation]) -> Error {
+    let reasons: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+    Error::Other(format!("Spending policy refused: {}", reasons.join(", ")))
+}
+
+fn today() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() / DAY,
+        Err(_) => 0,
+    }
+}
+
+fn db<T>(result: rusqlite::Result<T>) -> Result<T, Error> {
+    match result {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::policy::{Approver, Guard, Policy, Spend, Violation};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use substrate_subxt::sp_core::crypto::AccountId32;
+
+    #[test]
+    fn test_check() {
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+        let charlie = AccountId32::from([3; 32]);
+        let spend = |to: &AccountId32, amount: u128, spent_today: u128| Spend {
+            from: alice.clone(),
+            to: to.clone(),
+            amount: amount.into(),
+            spent_today: spent_today.into(),
+        };
+        let policy = Policy {
+            daily_limit: Some(1_000.into()),
+            max_amount: Some(600.into()),
+            allow: Some(vec![bob.clone(), charlie.clone()].into_iter().collect()),
+            deny: vec![charlie.clone()].into_iter().collect(),
+            approval_threshold: Some(300.into()),
+        };
+
+        assert_eq!(policy.check(&spend(&bob, 300, 0), None), vec![]);
+        assert_eq!(
+            policy.check(&spend(&charlie, 100, 0), None),
+            vec![Violation::Denied(charlie.clone())]
+        );
+        assert_eq!(
+            policy.check(&spend(&alice, 100, 0), None),
+            vec![Violation::NotAllowed(alice.clone())]
+        );
+        assert_eq!(
+            policy.check(&spend(&bob, 700, 400), None),
+            vec![
+                Violation::AboveMaximum {
+                    amount: 700.into(),
+                    maximum: 600.into(),
+                },
+                Violation::DailyLimit {
+                    spent: 400.into(),
+                    amount: 700.into(),
+                    limit: 1_000.into(),
+                },
+            ]
+        );
+
+        // the approver is asked only above the threshold and if nothing else
+        // is wrong
+        let asked = AtomicUsize::new(0);
+        let approver = |spend: &Spend| {
+            asked.fetch_add(1, Ordering::SeqCst);
+            spend.amount.plancks() <= 500
+        };
+        let approver = Some(&approver as &dyn Approver);
+        assert_eq!(policy.check(&spend(&bob, 300, 0), approver), vec![]);
+        assert_eq!(policy.check(&spend(&bob, 500, 0), approver), vec![]);
+        assert_eq!(
+            policy.check(&spend(&bob, 550, 0), approver),
+            vec![Violation::NotApproved {
+                amount: 550.into(),
+                threshold: 300.into(),
+            }]
+        );
+        assert_eq!(policy.check(&spend(&charlie, 550, 0), approver).len(), 1);
+        assert_eq!(asked.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            policy.check(&spend(&bob, 400, 0), None),
+            vec![Violation::NotApproved {
+                amount: 400.into(),
+                threshold: 300.into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_daily_counters() {
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+        let policy = Policy {
+            daily_limit: Some(1_000.into()),
+            ..Default::default()
+        };
+        let mut guard = Guard::open(":memory:", policy)
+            .unwrap()
+            .with_policy(bob.clone(), Policy::default());
+
+        guard
+            .authorize_on(&alice, &[(bob.clone(), 600.into())], 10)
+            .unwrap();
+        assert_eq!(
+            super::spent_on(&guard.conn, &alice, 10).unwrap(),
+            600.into()
+        );
+        assert!(guard
+            .authorize_on(&alice, &[(bob.clone(), 500.into())], 10)
+            .is_err());
+        guard
+            .authorize_on(&alice, &[(bob.clone(), 400.into())], 10)
+            .unwrap();
+        assert!(guard
+            .authorize_on(&alice, &[(bob.clone(), 1.into())], 10)
+            .is_err());
+        assert_eq!(
+            super::spent_on(&guard.conn, &alice, 10).unwrap(),
+            1_000.into()
+        );
+
+        // the counter starts over the next day
+        assert_eq!(super::spent_on(&guard.conn, &alice, 11).unwrap(), 0.into());
+        guard
+            .authorize_on(&alice, &[(bob.clone(), 900.into())], 11)
+            .unwrap();
+        assert_eq!(
+            super::spent_on(&guard.conn, &alice, 11).unwrap(),
+            900.into()
+        );
+
+        // the policy of bob has no limit
+        guard
+            .authorize_on(&bob, &[(alice.clone(), 5_000.into())], 11)
+            .unwrap();
+        assert_eq!(
+            super::spent_on(&guard.conn, &bob, 11).unwrap(),
+            5_000.into()
+        );
+        assert_eq!(
+            super::spent_on(&guard.conn, &alice, 11).unwrap(),
+            900.into()
+        );
+    }
+
+    #[test]
+    fn test_authorize_all() {
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+        let charlie = AccountId32::from([3; 32]);
+        let policy = Policy {
+            daily_limit: Some(1_000.into()),
+            max_amount: Some(500.into()),
+            deny: vec![charlie.clone()].into_iter().collect(),
+            approval_threshold: Some(300.into()),
+            ..Default::default()
+        };
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = asked.clone();
+        let mut guard =
+            Guard::open(":memory:", policy)
+                .unwrap()
+                .with_approver(move |spend: &Spend| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    spend.amount.plancks() <= 400
+                });
+        let spent = |guard: &Guard| super::spent_on(&guard.conn, &alice, 10).unwrap();
+
+        // one denied spend refuses all of them, before asking the approver
+        let spends = vec![(bob.clone(), 400.into()), (charlie.clone(), 100.into())];
+        assert!(guard.authorize_on(&alice, &spends, 10).is_err());
+        assert_eq!(asked.load(Ordering::SeqCst), 0);
+        assert_eq!(spent(&guard), 0.into());
+
+        let spends = vec![(bob.clone(), 400.into()), (bob.clone(), 400.into())];
+        guard.authorize_on(&alice, &spends, 10).unwrap();
+        assert_eq!(asked.load(Ordering::SeqCst), 2);
+        assert_eq!(spent(&guard), 800.into());
+
+        // the total is over the daily limit, none of them is counted
+        let spends = vec![(bob.clone(), 100.into()), (bob.clone(), 200.into())];
+        assert!(guard.authorize_on(&alice, &spends, 10).is_err());
+        assert_eq!(spent(&guard), 800.into());
+    }
+
+    #[test]
+    fn test_refund() {
+        let alice = AccountId32::from([1; 32]);
+        let bob = AccountId32::from([2; 32]);
+        let policy = Policy {
+            daily_limit: Some(1_000.into()),
+            ..Default::default()
+        };
+        let mut guard = Guard::open(":memory:", policy).unwrap();
+        let spent = |guard: &Guard, day| super::spent_on(&guard.conn, &alice, day).unwrap();
+
+        let spends = vec![(bob.clone(), 600.into()), (bob.clone(), 400.into())];
+        guard.authorize_on(&alice, &spends, 10).unwrap();
+        guard.refund_on(&alice, 400.into(), 10).unwrap();
+        assert_eq!(spent(&guard, 10), 600.into());
+        // what was not sent can be authorized again
+        guard
+            .authorize_on(&alice, &[(bob.clone(), 400.into())], 10)
+            .unwrap();
+        assert_eq!(spent(&guard, 10), 1_000.into());
+
+        // a refund never goes below zero
+        guard.refund_on(&alice, 5_000.into(), 10).unwrap();
+        assert_eq!(spent(&guard, 10), 0.into());
+
+        // a refund of yesterday leaves the counter of today alone
+        guard
+            .authorize_on(&alice, &[(bob.clone(), 300.into())], 11)
+            .unwrap();
+        guard.refund_on(&alice, 300.into(), 10).unwrap();
+        assert_eq!(spent(&guard, 11), 300.into());
+    }
+}
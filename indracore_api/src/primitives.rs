@@ -14,3 +14,28 @@ pub fn url() -> String {
     let url = env::var("RPC");
     url.unwrap_or("ws://127.0.0.1:9944".to_string())
 }
+
+/// number of decimals of the chain token, at most 38 so that one token unit
+/// fits in a u128
+pub fn decimals() -> u8 {
+    dotenv::dotenv().ok();
+    match env::var("DECIMAL").map(|decimal| decimal.parse()) {
+        Ok(Ok(decimal)) if decimal <= 38 => decimal,
+        _ => 15,
+    }
+}
+
+/// symbol of the chain token
+pub fn symbol() -> String {
+    dotenv::dotenv().ok();
+    env::var("SYMBOL").unwrap_or("SEL".to_string())
+}
+
+/// SS58 address format of the chain, `42` is the generic substrate format
+pub fn ss58_format() -> u8 {
+    dotenv::dotenv().ok();
+    match env::var("SS58") {
+        Ok(format) => format.parse().unwrap_or(42),
+        Err(_) => 42,
+    }
+}
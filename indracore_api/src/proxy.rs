@@ -0,0 +1,297 @@
+use crate::{
+    balance::units::Balance,
+    contract::{call::ContarctCall, deploy::ContractDeploy, instantiate::Instantiate},
+    frame::proxy::*,
+    primitives,
+    signer::ToSigner,
+};
+use scale::Decode;
+use substrate_subxt::{
+    balances::TransferCall,
+    contracts::{CallCall, InstantiateCall, PutCodeCall},
+    sp_core::crypto::AccountId32,
+    sp_runtime::DispatchResult,
+    Call, ClientBuilder, Error, ExtrinsicSuccess, IndracoreNodeRuntime, RawEvent,
+};
+
+pub use crate::frame::proxy::{ProxyDefinition, ProxyType};
+
+/// Modules whose calls can move funds of the real account. `Proxied::run`
+/// refuses them, so spends only go through the typed methods a `Guard` wraps.
+const SPENDING_MODULES: &[&str] = &[
+    "Balances",
+    "Contracts",
+    "Multisig",
+    "Proxy",
+    "Sudo",
+    "Utility",
+    "Vesting",
+];
+
+/// Submit operations signed by a proxy on behalf of the `real` account.
+pub struct Proxied {
+    pub signer: primitives::Sr25519,
+    pub real: AccountId32,
+    pub force_proxy_type: Option<ProxyType>,
+}
+
+impl Proxied {
+    /// wrap a call of the runtime in `proxy.proxy` and wait for it to be
+    /// included, calls of modules that can move funds are refused, use
+    /// `transfer`, `instantiate` or `call` for those
+    pub fn run<C>(&self, call: C) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+    where
+        C: Call<IndracoreNodeRuntime> + Send + Sync,
+    {
+        if SPENDING_MODULES.contains(&C::MODULE) {
+            return Err(Error::Other(format!(
+                "Proxied calls of the {} module are not allowed",
+                C::MODULE
+            )));
+        }
+        self.proxy(call)
+    }
+
+    fn proxy<C>(&self, call: C) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error>
+    where
+        C: Call<IndracoreNodeRuntime> + Send + Sync,
+    {
+        async_std::task::block_on(async move {
+            let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+                .set_url(primitives::url())
+                .build()
+                .await
+            {
+                Ok(cli) => cli,
+                Err(e) => return Err(e),
+            };
+            let inner = client.encode(call)?;
+            let result = client
+                .watch(
+                    ProxyCall {
+                        real: self.real.clone(),
+                        force_proxy_type: self.force_proxy_type,
+                        call: &inner,
+                    },
+                    &self.signer,
+                )
+                .await?;
+
+            executed(&result.events)?;
+            Ok(result)
+        })
+    }
+
+    pub fn transfer(
+        &self,
+        reciever: &primitives::IndracoreId,
+        amount: impl Into<Balance>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        self.proxy(TransferCall {
+            to: reciever,
+            amount: amount.into().plancks(),
+        })
+    }
+
+    /// upload the contract code of `deploy`, its signer is not used
+    pub fn put_code<S: ToSigner>(
+        &self,
+        deploy: &ContractDeploy<S>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let code = deploy.load_contract()?;
+        self.proxy(PutCodeCall {
+            _runtime: Default::default(),
+            code: &code,
+        })
+    }
+
+    /// instantiate a contract as described by `inst`, its signer is not used
+    pub fn instantiate<S: ToSigner>(
+        &self,
+        inst: &Instantiate<S>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let data = inst.encode()?;
+        self.proxy(InstantiateCall {
+            endowment: inst.endowment.plancks(),
+            gas_limit: inst.gas_limit,
+            code_hash: &inst.code_hash,
+            data: &data,
+        })
+    }
+
+    /// call a contract message as described by `call`, its signer is not used
+    pub fn call<S: ToSigner>(
+        &self,
+        call: &ContarctCall<S>,
+    ) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+        let data = call.encode()?;
+        self.proxy(CallCall {
+            dest: &call.contract,
+            value: call.value.plancks(),
+            gas_limit: call.gas_limit,
+            data: &data,
+        })
+    }
+}
+
+/// The outcome of the proxied call from the `ProxyExecuted` event of `proxy.proxy`.
+pub(crate) fn executed(events: &[RawEvent]) -> Result<(), Error> {
+    let executed = events
+        .iter()
+        .find(|e| e.module == "Proxy" && e.variant == "ProxyExecuted")
+        .ok_or_else(|| Error::Other("Failed to find a ProxyExecuted event".into()))?;
+    match DispatchResult::decode(&mut &executed.data[..]) {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(Error::Other(format!("Proxied call failed: {:?}", e))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// register `delegate` as proxy of the signer account
+pub fn add_proxy(
+    signer: &primitives::Sr25519,
+    delegate: AccountId32,
+    proxy_type: ProxyType,
+    delay: u32,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client
+            .watch(
+                AddProxyCall {
+                    delegate,
+                    proxy_type,
+                    delay,
+                },
+                signer,
+            )
+            .await
+    })
+}
+
+/// unregister `delegate` as proxy of the signer account
+pub fn remove_proxy(
+    signer: &primitives::Sr25519,
+    delegate: AccountId32,
+    proxy_type: ProxyType,
+    delay: u32,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client
+            .watch(
+                RemoveProxyCall {
+                    delegate,
+                    proxy_type,
+                    delay,
+                },
+                signer,
+            )
+            .await
+    })
+}
+
+/// unregister every proxy of the signer account
+pub fn remove_proxies(
+    signer: &primitives::Sr25519,
+) -> Result<ExtrinsicSuccess<IndracoreNodeRuntime>, Error> {
+    async_std::task::block_on(async move {
+        let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+            .set_url(primitives::url())
+            .build()
+            .await
+        {
+            Ok(cli) => cli,
+            Err(e) => return Err(e),
+        };
+        client
+            .watch(
+                RemoveProxiesCall {
+                    _runtime: Default::default(),
+                },
+                signer,
+            )
+            .await
+    })
+}
+
+/// list the proxies registered for `id`
+pub async fn proxies(id: AccountId32) -> Result<Vec<ProxyDefinition<AccountId32, u32>>, Error> {
+    let client = match ClientBuilder::<IndracoreNodeRuntime>::new()
+        .set_url(primitives::url())
+        .build()
+        .await
+    {
+        Ok(cli) => cli,
+        Err(e) => return Err(e),
+    };
+
+    let (proxies, _deposit) = match client.proxies(&id, None).await {
+        Ok(proxies) => proxies,
+        Err(e) => return Err(e),
+    };
+    Ok(proxies)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        primitives::IndracoreId,
+        proxy::{executed, Proxied},
+    };
+    use scale::Encode;
+    use substrate_subxt::{
+        balances::TransferCall,
+        sp_core::{crypto::AccountId32, sr25519, Pair},
+        sp_runtime::{DispatchError, DispatchResult},
+        IndracoreNodeRuntime, PairSigner, RawEvent,
+    };
+
+    fn proxy_executed(result: DispatchResult) -> RawEvent {
+        RawEvent {
+            module: "Proxy".into(),
+            variant: "ProxyExecuted".into(),
+            data: result.encode(),
+        }
+    }
+
+    #[test]
+    fn test_executed() {
+        assert!(executed(&[proxy_executed(Ok(()))]).is_ok());
+        assert!(executed(&[proxy_executed(Err(DispatchError::BadOrigin))]).is_err());
+        assert!(executed(&[]).is_err());
+    }
+
+    #[test]
+    fn test_run_refuses_transfers() {
+        let proxied = Proxied {
+            signer: PairSigner::<IndracoreNodeRuntime, sr25519::Pair>::new(
+                sr25519::Pair::from_string("//Alice", None).unwrap(),
+            ),
+            real: AccountId32::from([1; 32]),
+            force_proxy_type: None,
+        };
+        let to = IndracoreId::Id(AccountId32::from([2; 32]));
+        // refused before connecting to the node
+        assert!(proxied
+            .run(TransferCall {
+                to: &to,
+                amount: 1_000,
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::primitives;
+use jsonrpsee::{
+    common::{to_value as to_json_value, Params},
+    Client,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use substrate_subxt::{
+    sp_core::{storage::StorageKey, twox_128, Bytes, H256},
+    Error,
+};
+
+/// Connect a raw JSON-RPC client to the node, for the calls substrate-subxt does not expose.
+pub async fn client() -> Result<Client, Error> {
+    match jsonrpsee::ws_client(&primitives::url()).await {
+        Ok(cli) => Ok(cli),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+pub async fn request<R: DeserializeOwned>(
+    client: &Client,
+    method: &str,
+    params: Vec<serde_json::Value>,
+) -> Result<R, Error> {
+    match client.request(method, Params::Array(params)).await {
+        Ok(r) => Ok(r),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn json<V: serde::Serialize>(value: V) -> Result<serde_json::Value, Error> {
+    match to_json_value(value) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(Error::Other(format!("{:?}", e))),
+    }
+}
+
+/// read the plain storage value `module::name` at block `at`
+pub async fn storage_value(
+    client: &Client,
+    module: &str,
+    name: &str,
+    at: H256,
+) -> Result<Option<Bytes>, Error> {
+    let mut key = twox_128(module.as_bytes()).to_vec();
+    key.extend(twox_128(name.as_bytes()).iter());
+    request(
+        client,
+        "state_getStorage",
+        vec![json(StorageKey(key))?, json(at)?],
+    )
+    .await
+}
+
+/// The dispatch info and fee of an extrinsic as returned by `payment_queryInfo`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeDispatchInfo {
+    pub weight: u64,
+    pub class: DispatchClass,
+    #[serde(deserialize_with = "deserialize_balance")]
+    pub partial_fee: u128,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DispatchClass {
+    Normal,
+    Operational,
+    Mandatory,
+}
+
+/// The runtime versions an extrinsic has to be signed for.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+pub async fn runtime_version(client: &Client) -> Result<RuntimeVersion, Error> {
+    request(client, "state_getRuntimeVersion", vec![]).await
+}
+
+/// query the fee of the SCALE encoded extrinsic `encoded` at `at`, or the best block
+pub async fn payment_query_info(
+    client: &Client,
+    encoded: &[u8],
+    at: Option<H256>,
+) -> Result<RuntimeDispatchInfo, Error> {
+    let extrinsic = format!("0x{}", hex::encode(encoded));
+    request(
+        client,
+        "payment_queryInfo",
+        vec![json(extrinsic)?, json(at)?],
+    )
+    .await
+}
+
+/// Balances are sent as numbers by older nodes and as strings by newer ones.
+fn deserialize_balance<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Balance {
+        Number(u64),
+        String(String),
+    }
+
+    match Balance::deserialize(deserializer)? {
+        Balance::Number(n) => Ok(n as u128),
+        Balance::String(s) => {
+            let parsed = if s.starts_with("0x") {
+                u128::from_str_radix(s.trim_start_matches("0x"), 16)
+            } else {
+                s.parse()
+            };
+            parsed.map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rpc::{DispatchClass, RuntimeDispatchInfo};
+
+    #[test]
+    fn test_dispatch_info() {
+        let info: RuntimeDispatchInfo =
+            serde_json::from_str(r#"{"weight":195000000,"class":"normal","partialFee":125000000}"#)
+                .unwrap();
+        assert_eq!(info.class, DispatchClass::Normal);
+        assert_eq!(info.partial_fee, 125_000_000);
+
+        let info: RuntimeDispatchInfo = serde_json::from_str(
+            r#"{"weight":195000000,"class":"operational","partialFee":"340282366920938463463374607431768211455"}"#,
+        )
+        .unwrap();
+        assert_eq!(info.partial_fee, u128::max_value());
+    }
+}
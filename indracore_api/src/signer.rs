@@ -0,0 +1,315 @@
+use crate::{keyring, primitives};
+use async_std::{
+    io::{self, prelude::*, BufReader},
+    os::unix::net::UnixStream,
+};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, path::PathBuf, pin::Pin, sync::Arc, time::Duration};
+use substrate_subxt::{
+    sp_core::{crypto::AccountId32, ecdsa, ed25519, sr25519},
+    sp_runtime::MultiSignature,
+    Error, IndracoreNodeRuntime, SignedPayload, Signer, UncheckedExtrinsic,
+};
+
+/// Signs payloads with a key that lives outside of this process.
+pub trait ExternalSigner: Send + Sync {
+    /// The account the signatures are made for.
+    fn account_id(&self) -> &AccountId32;
+
+    /// Sign the SCALE encoded signing payload of an extrinsic. Payloads longer
+    /// than 256 bytes are already blake2-256 hashed, as the runtime expects.
+    fn sign_payload(
+        &self,
+        payload: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<MultiSignature, String>> + Send + Sync>>;
+}
+
+/// Adapts an `ExternalSigner` to the signer interface of substrate-subxt.
+pub struct RemoteSigner<S: ExternalSigner> {
+    inner: Arc<S>,
+    account_id: AccountId32,
+    nonce: Option<u32>,
+}
+
+impl<S: ExternalSigner> RemoteSigner<S> {
+    pub fn new(inner: S) -> Self {
+        let account_id = inner.account_id().clone();
+        RemoteSigner {
+            inner: Arc::new(inner),
+            account_id,
+            nonce: None,
+        }
+    }
+
+    pub fn set_nonce(&mut self, nonce: u32) {
+        self.nonce = Some(nonce);
+    }
+
+    pub fn increment_nonce(&mut self) {
+        self.nonce = self.nonce.map(|nonce| nonce + 1);
+    }
+}
+
+impl<S: ExternalSigner> Clone for RemoteSigner<S> {
+    fn clone(&self) -> Self {
+        RemoteSigner {
+            inner: self.inner.clone(),
+            account_id: self.account_id.clone(),
+            nonce: self.nonce,
+        }
+    }
+}
+
+impl<S: ExternalSigner + 'static> Signer<IndracoreNodeRuntime> for RemoteSigner<S> {
+    fn account_id(&self) -> &AccountId32 {
+        &self.account_id
+    }
+
+    fn nonce(&self) -> Option<u32> {
+        self.nonce
+    }
+
+    fn sign(
+        &self,
+        extrinsic: SignedPayload<IndracoreNodeRuntime>,
+    ) -> Pin<
+        Box<
+            dyn Future<Output = Result<UncheckedExtrinsic<IndracoreNodeRuntime>, String>>
+                + Send
+                + Sync,
+        >,
+    > {
+        let payload = extrinsic.using_encoded(|payload| payload.to_vec());
+        let (call, extra, _) = extrinsic.deconstruct();
+        let account_id = self.account_id.clone();
+        let signature = self.inner.sign_payload(payload);
+        Box::pin(async move {
+            let signature = signature.await?;
+            Ok(UncheckedExtrinsic::<IndracoreNodeRuntime>::new_signed(
+                call,
+                account_id.into(),
+                signature,
+                extra,
+            ))
+        })
+    }
+}
+
+/// Anything the crate can sign extrinsics with.
+pub trait ToSigner {
+    type Signer: Signer<IndracoreNodeRuntime> + Send + Sync;
+
+    /// `pass` is the password of the secret, if it has one
+    fn to_signer(&self, pass: Option<&str>) -> Result<Self::Signer, Error>;
+}
+
+impl ToSigner for keyring::Sr25519 {
+    type Signer = primitives::Sr25519;
+
+    fn to_signer(&self, pass: Option<&str>) -> Result<Self::Signer, Error> {
+        self.pair(pass)
+    }
+}
+
+impl ToSigner for keyring::Ed25519 {
+    type Signer = primitives::Ed25519;
+
+    fn to_signer(&self, pass: Option<&str>) -> Result<Self::Signer, Error> {
+        self.pair(pass)
+    }
+}
+
+impl<S: ExternalSigner + 'static> ToSigner for RemoteSigner<S> {
+    type Signer = RemoteSigner<S>;
+
+    fn to_signer(&self, _pass: Option<&str>) -> Result<Self::Signer, Error> {
+        Ok(self.clone())
+    }
+}
+
+/// How long a `UnixSocketSigner` waits for the daemon to connect, read the
+/// request and answer it.
+pub const DEFAULT_SIGN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct SignRequest {
+    account_id: String,
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    scheme: Option<String>,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// Asks a local signing daemon for signatures over a Unix socket.
+///
+/// Every request is a single line of JSON `{"account_id": "0x..", "payload": "0x.."}`
+/// and is answered with a single line `{"scheme": "sr25519", "signature": "0x.."}`
+/// or `{"error": ".."}`. The scheme is one of `sr25519`, `ed25519` or `ecdsa`
+/// and is required.
+pub struct UnixSocketSigner {
+    pub path: PathBuf,
+    pub account_id: AccountId32,
+    /// limit on each of connecting, sending the request and reading the answer
+    pub timeout: Duration,
+}
+
+impl UnixSocketSigner {
+    pub fn new(path: PathBuf, account_id: AccountId32) -> Self {
+        UnixSocketSigner {
+            path,
+            account_id,
+            timeout: DEFAULT_SIGN_TIMEOUT,
+        }
+    }
+}
+
+impl ExternalSigner for UnixSocketSigner {
+    fn account_id(&self) -> &AccountId32 {
+        &self.account_id
+    }
+
+    fn sign_payload(
+        &self,
+        payload: Vec<u8>,
+    ) -> Pin<Box<dyn Future<Output = Result<MultiSignature, String>> + Send + Sync>> {
+        let path = self.path.clone();
+        let timeout = self.timeout;
+        let account_id: &[u8] = self.account_id.as_ref();
+        let request = SignRequest {
+            account_id: format!("0x{}", hex::encode(account_id)),
+            payload: format!("0x{}", hex::encode(&payload)),
+        };
+        Box::pin(async move {
+            let mut line = match serde_json::to_string(&request) {
+                Ok(line) => line,
+                Err(e) => return Err(format!("{:?}", e)),
+            };
+            line.push('\n');
+
+            let mut stream = match io::timeout(timeout, UnixStream::connect(&path)).await {
+                Ok(stream) => stream,
+                Err(e) => return Err(format!("{:?}", e)),
+            };
+            if let Err(e) = io::timeout(timeout, stream.write_all(line.as_bytes())).await {
+                return Err(format!("{:?}", e));
+            }
+
+            let mut response = String::new();
+            let mut reader = BufReader::new(stream);
+            if let Err(e) = io::timeout(timeout, reader.read_line(&mut response)).await {
+                return Err(format!("{:?}", e));
+            }
+            let response: SignResponse = match serde_json::from_str(&response) {
+                Ok(r) => r,
+                Err(e) => return Err(format!("{:?}", e)),
+            };
+            if let Some(e) = response.error {
+                return Err(e);
+            }
+            let scheme = match response.scheme {
+                Some(scheme) => scheme,
+                None => return Err("Signing daemon returned no signature scheme".into()),
+            };
+            let signature = match response.signature {
+                Some(sig) => sig,
+                None => return Err("Signing daemon returned no signature".into()),
+            };
+            parse_signature(&scheme, &signature)
+        })
+    }
+}
+
+/// Build a `MultiSignature` from its scheme name and hex encoded bytes.
+pub fn parse_signature(scheme: &str, signature: &str) -> Result<MultiSignature, String> {
+    let bytes = match crate::util::decode_hex(signature) {
+        Ok(b) => b,
+        Err(e) => return Err(format!("{:?}", e)),
+    };
+    match (scheme, bytes.len()) {
+        ("sr25519", 64) => {
+            let mut raw = [0u8; 64];
+            raw.copy_from_slice(&bytes);
+            Ok(sr25519::Signature::from_raw(raw).into())
+        }
+        ("ed25519", 64) => {
+            let mut raw = [0u8; 64];
+            raw.copy_from_slice(&bytes);
+            Ok(ed25519::Signature::from_raw(raw).into())
+        }
+        ("ecdsa", 65) => {
+            let mut raw = [0u8; 65];
+            raw.copy_from_slice(&bytes);
+            Ok(ecdsa::Signature::from_raw(raw).into())
+        }
+        (scheme, len) => Err(format!("Invalid {} signature of {} bytes", scheme, len)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::signer::{parse_signature, ExternalSigner, UnixSocketSigner};
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixListener,
+        time::Duration,
+    };
+    use substrate_subxt::sp_runtime::MultiSignature;
+
+    fn answer(name: &str, response: Option<String>) -> UnixSocketSigner {
+        let mut path = std::env::temp_dir();
+        path.push(format!("indracore-signer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            match response {
+                Some(response) => writeln!(reader.get_mut(), "{}", response).unwrap(),
+                // keep the connection open without answering
+                None => std::thread::sleep(Duration::from_secs(5)),
+            }
+        });
+        let mut signer = UnixSocketSigner::new(path, [1u8; 32].into());
+        signer.timeout = Duration::from_millis(200);
+        signer
+    }
+
+    #[test]
+    fn test_parse_signature() {
+        let sig = format!("0x{}", "11".repeat(64));
+        assert!(matches!(
+            parse_signature("sr25519", &sig),
+            Ok(MultiSignature::Sr25519(_))
+        ));
+        assert!(matches!(
+            parse_signature("ed25519", &sig),
+            Ok(MultiSignature::Ed25519(_))
+        ));
+        assert!(parse_signature("ecdsa", &sig).is_err());
+        assert!(parse_signature("sr25519", "0x1111").is_err());
+    }
+
+    #[test]
+    fn test_unix_socket_signer() {
+        let sig = format!("0x{}", "11".repeat(64));
+        let response = format!(r#"{{"scheme": "ed25519", "signature": "{}"}}"#, sig);
+        let signer = answer("ok", Some(response));
+        assert!(matches!(
+            async_std::task::block_on(signer.sign_payload(vec![0])),
+            Ok(MultiSignature::Ed25519(_))
+        ));
+
+        let response = format!(r#"{{"signature": "{}"}}"#, sig);
+        let signer = answer("no-scheme", Some(response));
+        assert!(async_std::task::block_on(signer.sign_payload(vec![0])).is_err());
+
+        let signer = answer("silent", None);
+        assert!(async_std::task::block_on(signer.sign_payload(vec![0])).is_err());
+    }
+}
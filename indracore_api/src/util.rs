@@ -24,3 +24,32 @@ pub fn decode_hex(input: &str) -> Result<Vec<u8>, hex::FromHexError> {
         hex::decode(input)
     }
 }
+
+/// Format an amount of plancks in token units with `decimals` decimal places,
+/// dropping trailing zeros.
+pub fn format_units(amount: u128, decimals: u8) -> String {
+    // above 38 decimals one token unit does not fit in a u128
+    let (whole, fraction) = match 10u128.checked_pow(decimals as u32) {
+        Some(unit) => (amount / unit, amount % unit),
+        None => (0, amount),
+    };
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::format_units;
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(0, 15), "0");
+        assert_eq!(format_units(1_500_000_000_000_000, 15), "1.5");
+        assert_eq!(format_units(12_500_000_000_000, 15), "0.0125");
+        assert_eq!(format_units(42, 0), "42");
+        assert_eq!(format_units(5, 40), format!("0.{}5", "0".repeat(39)));
+    }
+}